@@ -0,0 +1,82 @@
+//! Evaluation of programs declaring thousands of derived units
+
+use std::io;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use digify::{Interpreter, Unit};
+
+const AXIOMS: [(&str, &str); 5] = [("D", "m"), ("T", "s"), ("M", "kg"), ("I", "A"), ("K", "K")];
+
+/// Ident of the `i`-th unit, made of letters only as digits are exponents
+fn name(mut i: usize) -> String {
+    let mut name = String::from("U");
+    loop {
+        name.push((b'a' + (i % 26) as u8) as char);
+        i /= 26;
+        if i == 0 {
+            break name;
+        }
+    }
+}
+
+/// `count` units, each built from the two previous ones and an axiom, then checked
+/// against their simplification
+fn chain(count: usize) -> String {
+    let mut program = String::new();
+    for (ident, symbol) in AXIOMS {
+        program.push_str(&format!("define {} \"{}\";\n", ident, symbol));
+    }
+
+    for i in 0..count {
+        let axiom = AXIOMS[i % AXIOMS.len()].0;
+        let unit = match i {
+            0 => axiom.to_string(),
+            1 => format!("{} {}", name(0), axiom),
+            _ => format!("{} / {} * {}", name(i - 1), name(i - 2), axiom),
+        };
+        program.push_str(&format!("let {} = {};\n", name(i), unit));
+    }
+
+    for i in 0..count {
+        program.push_str(&format!("assert {} %{};\n", name(i), name(i)));
+    }
+
+    program
+}
+
+fn eval(program: &str) -> Interpreter<'static> {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(io::sink());
+    interpreter.eval_str(program).unwrap();
+
+    interpreter
+}
+
+fn units(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chain");
+    for count in [1000, 4000] {
+        let program = chain(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &program, |b, program| {
+            b.iter(|| eval(program))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("product");
+    for count in [1000, 4000] {
+        let mut interpreter = eval(&chain(count));
+        let units = (0..count)
+            .map(|i| {
+                let unit = interpreter.parse_unit(name(i)).unwrap();
+                interpreter.simplify(&unit).unwrap()
+            })
+            .collect::<Vec<_>>();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &units, |b, units| {
+            b.iter(|| units.iter().cloned().try_fold(Unit::default(), Unit::checked_mul))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, units);
+criterion_main!(benches);
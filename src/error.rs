@@ -1,57 +1,289 @@
 use core::fmt;
 use std::error::Error;
+use std::time::Duration;
 
 use crate::parser::Span;
 
-pub type Result<'a, T> = color_eyre::Result<T, DigifyError<'a>>;
+pub type Result<T> = std::result::Result<T, DigifyError>;
 
 #[derive(Debug)]
-pub struct DigifyError<'a> {
+pub struct DigifyError {
     kind: ErrorKind,
-    span: Span<'a>,
+    span: Span,
+    labels: Vec<Label>,
+    /// Names that may have been meant instead of the one of the error, rarely any, boxed
+    /// to keep the results small
+    suggestions: Box<[String]>,
 }
 
-impl<'a> DigifyError<'a> {
-    pub fn new(kind: ErrorKind, span: Span<'a>) -> Self {
-        Self { kind, span }
+/// Other place of the source related to an error, like the first declaration of a variable
+/// declared twice
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Why both sides of an `assert` differ
+#[derive(Debug, Clone)]
+pub struct AssertFailure {
+    /// Left side as printed
+    pub expected: String,
+    /// Right side as printed
+    pub actual: String,
+    /// Left side reduced to axioms
+    pub expected_axioms: String,
+    /// Right side reduced to axioms
+    pub actual_axioms: String,
+    /// Symbol of each axiom of either side with its exponent on the left and on the right
+    pub exponents: Vec<(String, i32, i32)>,
+    /// Left side divided by the right one, `None` if an exponent overflows
+    pub residual: Option<String>,
+}
+
+impl AssertFailure {
+    /// Table of the exponents of each axiom on both sides, the different ones marked
+    fn table(&self) -> String {
+        let headers = ("axiom", "left", "right");
+        let name_width = self
+            .exponents
+            .iter()
+            .map(|(name, _, _)| name.chars().count())
+            .fold(headers.0.len(), usize::max);
+        let number_width = |header: &str, exponent: fn(&(String, i32, i32)) -> i32| {
+            self.exponents
+                .iter()
+                .map(|row| exponent(row).to_string().len())
+                .fold(header.len(), usize::max)
+        };
+        let left_width = number_width(headers.1, |(_, left, _)| *left);
+        let right_width = number_width(headers.2, |(_, _, right)| *right);
+
+        let mut table = format!(
+            "{:name_width$}  {:>left_width$}  {:>right_width$}",
+            headers.0, headers.1, headers.2
+        );
+        for (name, left, right) in &self.exponents {
+            let row = format!("{name:name_width$}  {left:>left_width$}  {right:>right_width$}");
+            let marker = if left == right { "" } else { "  ≠" };
+            table += &format!("\n{}{}", row, marker);
+        }
+
+        table
     }
 }
 
+impl DigifyError {
+    pub fn new(kind: ErrorKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            labels: Vec::new(),
+            suggestions: Box::default(),
+        }
+    }
+
+    /// The error with a secondary label at `span`
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions.into_boxed_slice();
+        self
+    }
+
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// What went wrong, new kinds of errors may be added
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
-    AssertFail(String, String),
+    AssertFail(Box<AssertFailure>),
     VariableAlreadyDeclared(String),
     VariableNotDeclared(String),
+    /// Name given twice in a list of variables, like the ones of `pi`
+    RepeatedVariable(String),
+    NoSolution(String, String),
+    CannotExpress(String, String),
+    ExponentOverflow,
+    StatementLimit(usize),
+    IdentLimit(usize),
+    ExponentLimit(u32),
+    DepthLimit(usize),
+    TimeLimit(Duration),
+    /// Choices of exponents a search would enumerate, over the limit
+    SearchLimit(u64),
+    /// Names of the definitions around a cycle, the first one repeated at the end
+    CyclicDefinitions(Vec<String>),
+    /// Input that does not fit the grammar, with what could have come instead
+    UnexpectedToken {
+        expected: Vec<String>,
+        found: String,
+    },
+    /// Keyword where an identifier was expected
+    ReservedIdent(String),
+    UnterminatedString,
+    /// Other syntax error, like an input too deeply nested for the parser
+    Syntax(String),
+    Output(String),
 }
 
+impl ErrorKind {
+    /// Short text under the span of the error, the message saying the rest
+    pub fn label(&self) -> Option<String> {
+        match self {
+            Self::VariableAlreadyDeclared(_) => Some("declared again here".into()),
+            Self::VariableNotDeclared(_) => Some("not declared".into()),
+            Self::RepeatedVariable(_) => Some("given again here".into()),
+            Self::ExponentOverflow => Some("overflows".into()),
+            Self::CyclicDefinitions(names) => Some(format!("uses {}", names[1])),
+            _ => None,
+        }
+    }
+
+    /// Name of the kind in machine-readable reports, like `variable_not_declared`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AssertFail(_) => "assert_fail",
+            Self::VariableAlreadyDeclared(_) => "variable_already_declared",
+            Self::VariableNotDeclared(_) => "variable_not_declared",
+            Self::RepeatedVariable(_) => "repeated_variable",
+            Self::NoSolution(..) => "no_solution",
+            Self::CannotExpress(..) => "cannot_express",
+            Self::ExponentOverflow => "exponent_overflow",
+            Self::StatementLimit(_) => "statement_limit",
+            Self::IdentLimit(_) => "ident_limit",
+            Self::ExponentLimit(_) => "exponent_limit",
+            Self::DepthLimit(_) => "depth_limit",
+            Self::TimeLimit(_) => "time_limit",
+            Self::SearchLimit(_) => "search_limit",
+            Self::CyclicDefinitions(_) => "cyclic_definitions",
+            Self::UnexpectedToken { .. } => "unexpected_token",
+            Self::ReservedIdent(_) => "reserved_ident",
+            Self::UnterminatedString => "unterminated_string",
+            Self::Syntax(_) => "syntax",
+            Self::Output(_) => "output",
+        }
+    }
 
-impl<'a> fmt::Display for DigifyError<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let padding = if self.span.start().col() - 1 == 0 {
-            ""
-        } else {
-            " ... "
-        };
+    /// Explanations following the lines of the error, each one possibly over several lines
+    pub fn notes(&self) -> Vec<String> {
+        match self {
+            Self::AssertFail(failure) => vec![
+                format!(
+                    "in axioms, {} on the left and {} on the right",
+                    failure.expected_axioms, failure.actual_axioms
+                ),
+                format!("exponent of each axiom:\n{}", failure.table()),
+            ],
+            _ => Vec::new(),
+        }
+    }
 
-        write!(f, 
-            "{} | {}{}\n\t",
-            self.span.start().line(),
-            padding,
-            self.span.input()
-        )?;
+    /// Whether the evaluation stops on this error rather than going on with the next
+    /// statements: the statement and time limits, and a broken output
+    pub fn stops_evaluation(&self) -> bool {
+        matches!(
+            self,
+            Self::StatementLimit(_) | Self::TimeLimit(_) | Self::Output(_)
+        )
+    }
+}
 
-        match &self.kind {
-            ErrorKind::AssertFail(expected, actual) => {
-                write!(f, "Assertion failed: expected {}, got {}", expected, actual)
+/// Only the message, [`SourceMap::report`](crate::SourceMap::report) also shows where it happened
+impl fmt::Display for DigifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AssertFail(failure) => {
+                let AssertFailure {
+                    expected, actual, ..
+                } = &**failure;
+                write!(f, "Assertion failed: expected {}, got {}", expected, actual)?;
+                match &failure.residual {
+                    Some(residual) => write!(f, ", off by a factor of {}", residual),
+                    None => Ok(()),
+                }
             }
-            ErrorKind::VariableAlreadyDeclared(ident) => {
+            Self::VariableAlreadyDeclared(ident) => {
                 write!(f, "Variable {} already declared", ident)
             }
-            ErrorKind::VariableNotDeclared(ident) => {
+            Self::VariableNotDeclared(ident) => {
                 write!(f, "Variable {} not declared", ident)
             }
+            Self::RepeatedVariable(ident) => {
+                write!(f, "Variable {} given twice in the list", ident)
+            }
+            Self::NoSolution(ident, reason) => {
+                write!(f, "No solution for {}: {}", ident, reason)
+            }
+            Self::CannotExpress(basis, reason) => {
+                write!(f, "Cannot express in [{}]: {}", basis, reason)
+            }
+            Self::ExponentOverflow => {
+                write!(f, "Exponent overflow: exponents must fit in a 32 bits integer")
+            }
+            Self::StatementLimit(max) => write!(f, "Too many statements: the limit is {}", max),
+            Self::IdentLimit(max) => {
+                write!(f, "Too many identifiers in a unit: the limit is {}", max)
+            }
+            Self::ExponentLimit(max) => write!(f, "Exponent too large: the limit is {}", max),
+            Self::DepthLimit(max) => {
+                write!(f, "Expression nested too deeply: the limit is {}", max)
+            }
+            Self::TimeLimit(budget) => write!(f, "Time budget of {:?} exceeded", budget),
+            Self::SearchLimit(max) => write!(
+                f,
+                "Search too large: more than {} choices of exponents, lower its max",
+                max
+            ),
+            Self::CyclicDefinitions(names) => {
+                write!(f, "Definitions depend on each other: {}", names.join(" -> "))
+            }
+            Self::UnexpectedToken { expected, found } if expected.is_empty() => {
+                write!(f, "Syntax error: unexpected {}", found)
+            }
+            Self::UnexpectedToken { expected, found } => {
+                let (last, others) = expected.split_last().unwrap();
+                let expected = match others {
+                    [] => last.clone(),
+                    _ => format!("{} or {}", others.join(", "), last),
+                };
+                write!(f, "Syntax error: expected {}, found {}", expected, found)
+            }
+            Self::ReservedIdent(keyword) => {
+                write!(f, "Syntax error: `{}` is a keyword and can not name a variable", keyword)
+            }
+            Self::UnterminatedString => write!(f, "Syntax error: string never closed"),
+            Self::Syntax(message) => write!(f, "Syntax error: {}", message),
+            Self::Output(message) => write!(f, "Can not print: {}", message),
         }
     }
 }
 
-impl<'a> Error for DigifyError<'a> {}
+impl Error for DigifyError {}
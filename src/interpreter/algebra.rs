@@ -0,0 +1,322 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A value of the elimination, or an exponent computed from it, does not fit its integer type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+type Checked<T> = Result<T, Overflow>;
+
+/// Exact rational number, always kept reduced with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    num: i64,
+    den: i64,
+}
+
+impl Ratio {
+    pub const ZERO: Ratio = Ratio { num: 0, den: 1 };
+    pub const ONE: Ratio = Ratio { num: 1, den: 1 };
+
+    pub fn new(num: i64, den: i64) -> Checked<Self> {
+        assert!(den != 0, "Ratio with a zero denominator");
+
+        let sign: i64 = if den < 0 { -1 } else { 1 };
+        let divisor = gcd(num, den)?.max(1);
+
+        Ok(Self {
+            num: checked(sign.checked_mul(num))? / divisor,
+            den: checked(sign.checked_mul(den))? / divisor,
+        })
+    }
+
+    pub fn num(&self) -> i64 {
+        self.num
+    }
+
+    pub fn den(&self) -> i64 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Checked<Self> {
+        let lhs = checked(self.num.checked_mul(rhs.den))?;
+        let rhs_num = checked(rhs.num.checked_mul(self.den))?;
+        let den = checked(self.den.checked_mul(rhs.den))?;
+
+        Self::new(checked(lhs.checked_add(rhs_num))?, den)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Checked<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Checked<Self> {
+        let num = checked(self.num.checked_mul(rhs.num))?;
+        let den = checked(self.den.checked_mul(rhs.den))?;
+
+        Self::new(num, den)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Checked<Self> {
+        let num = checked(self.num.checked_mul(rhs.den))?;
+        let den = checked(self.den.checked_mul(rhs.num))?;
+
+        Self::new(num, den)
+    }
+
+    pub fn checked_neg(self) -> Checked<Self> {
+        Ok(Self {
+            num: checked(self.num.checked_neg())?,
+            den: self.den,
+        })
+    }
+}
+
+impl From<i32> for Ratio {
+    fn from(value: i32) -> Self {
+        Self {
+            num: value as i64,
+            den: 1,
+        }
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Products of two i64 always fit in an i128
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn checked<T>(value: Option<T>) -> Checked<T> {
+    value.ok_or(Overflow)
+}
+
+pub fn gcd(a: i64, b: i64) -> Checked<i64> {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.try_into().map_err(|_| Overflow)
+}
+
+fn lcm(a: i64, b: i64) -> Checked<i64> {
+    checked((a / gcd(a, b)?).checked_mul(b))
+}
+
+/// Reduce `matrix` to its reduced row echelon form, returning the pivot column of each row
+fn rref(matrix: &mut [Vec<Ratio>]) -> Checked<Vec<usize>> {
+    let columns = matrix.first().map_or(0, Vec::len);
+    let mut pivots = Vec::new();
+
+    for column in 0..columns {
+        let row = pivots.len();
+        let Some(pivot) = (row..matrix.len()).find(|r| !matrix[*r][column].is_zero()) else {
+            continue;
+        };
+        matrix.swap(row, pivot);
+
+        let factor = matrix[row][column];
+        for value in matrix[row].iter_mut() {
+            *value = value.checked_div(factor)?;
+        }
+
+        let pivot_row = matrix[row].clone();
+        for (other, values) in matrix.iter_mut().enumerate() {
+            let factor = values[column];
+            if other != row && !factor.is_zero() {
+                for (value, pivot_value) in values.iter_mut().zip(&pivot_row) {
+                    *value = value.checked_sub(factor.checked_mul(*pivot_value)?)?;
+                }
+            }
+        }
+
+        pivots.push(column);
+    }
+
+    Ok(pivots)
+}
+
+/// Basis of the null space of `matrix`, each vector scaled to the smallest integers
+pub fn null_space(matrix: &[Vec<i32>], columns: usize) -> Checked<Vec<Vec<i32>>> {
+    let mut matrix = matrix
+        .iter()
+        .map(|row| row.iter().copied().map(Ratio::from).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let pivots = rref(&mut matrix)?;
+
+    (0..columns)
+        .filter(|column| !pivots.contains(column))
+        .map(|free| {
+            let mut vector = vec![Ratio::ZERO; columns];
+            vector[free] = Ratio::ONE;
+            for (row, pivot) in pivots.iter().enumerate() {
+                vector[*pivot] = matrix[row][free].checked_neg()?;
+            }
+
+            to_integers(&vector)
+        })
+        .collect()
+}
+
+/// Matrix `[matrix | rhs]`, its last column is the right-hand side
+fn augmented(matrix: &[Vec<i32>], rhs: &[i32]) -> Vec<Vec<Ratio>> {
+    matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, value)| {
+            row.iter()
+                .copied()
+                .chain(std::iter::once(*value))
+                .map(Ratio::from)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Solve `matrix * x = rhs`, returning `None` when the system has no solution.
+/// Free variables are set to zero.
+pub fn solve(matrix: &[Vec<i32>], rhs: &[i32], columns: usize) -> Checked<Option<Vec<Ratio>>> {
+    let mut augmented = augmented(matrix, rhs);
+    let pivots = rref(&mut augmented)?;
+
+    if pivots.contains(&columns) {
+        return Ok(None);
+    }
+
+    let mut solution = vec![Ratio::ZERO; columns];
+    for (row, pivot) in pivots.iter().enumerate() {
+        solution[*pivot] = augmented[row][columns];
+    }
+
+    Ok(Some(solution))
+}
+
+/// Every solution of `matrix * x = rhs`: the free unknowns take any value, and each of
+/// the others is an affine function of them
+#[derive(Debug)]
+pub struct Solutions {
+    /// Columns of the free unknowns
+    pub free: Vec<usize>,
+    pivots: Vec<Pivot>,
+}
+
+/// Unknown of a pivot column, `den * x[column] = constant - Σ coefficients[i] * x[free[i]]`
+#[derive(Debug)]
+struct Pivot {
+    column: usize,
+    den: i64,
+    constant: i64,
+    coefficients: Vec<i64>,
+}
+
+impl Solutions {
+    /// Set the pivot unknowns of `x` from its free ones, `false` if one of them is not an
+    /// integer within `-bound..=bound`
+    pub fn complete(&self, x: &mut [i32], bound: i32) -> bool {
+        for pivot in &self.pivots {
+            // Each term fits in an i128, and so does their sum
+            let sum = pivot
+                .coefficients
+                .iter()
+                .zip(&self.free)
+                .map(|(coefficient, free)| *coefficient as i128 * x[*free] as i128)
+                .sum::<i128>();
+            let value = pivot.constant as i128 - sum;
+            let den = pivot.den as i128;
+            if value % den != 0 || (value / den).unsigned_abs() > bound.unsigned_abs() as u128 {
+                return false;
+            }
+            x[pivot.column] = (value / den) as i32;
+        }
+
+        true
+    }
+}
+
+/// Solutions of `matrix * x = rhs`, `None` when the system has none
+pub fn solutions(matrix: &[Vec<i32>], rhs: &[i32], columns: usize) -> Checked<Option<Solutions>> {
+    let mut augmented = augmented(matrix, rhs);
+    let pivot_columns = rref(&mut augmented)?;
+
+    if pivot_columns.contains(&columns) {
+        return Ok(None);
+    }
+
+    let free = (0..columns)
+        .filter(|column| !pivot_columns.contains(column))
+        .collect::<Vec<_>>();
+    let pivots = pivot_columns
+        .iter()
+        .zip(&augmented)
+        .map(|(column, row)| {
+            let values = free.iter().map(|free| row[*free]).chain([row[columns]]);
+            let den = values.clone().try_fold(1, |acc, value| lcm(acc, value.den()))?;
+            let mut values = values
+                .map(|value| checked(value.num().checked_mul(den / value.den())))
+                .collect::<Checked<Vec<_>>>()?;
+            let constant = values.pop().unwrap();
+
+            Ok(Pivot {
+                column: *column,
+                den,
+                constant,
+                coefficients: values,
+            })
+        })
+        .collect::<Checked<Vec<_>>>()?;
+
+    Ok(Some(Solutions { free, pivots }))
+}
+
+/// Scale a rational vector to the smallest integer vector with the same direction,
+/// with its first non zero value positive
+fn to_integers(vector: &[Ratio]) -> Checked<Vec<i32>> {
+    let multiple = vector
+        .iter()
+        .try_fold(1, |acc, value| lcm(acc, value.den()))?;
+    let integers = vector
+        .iter()
+        .map(|value| checked(value.num().checked_mul(multiple / value.den())))
+        .collect::<Checked<Vec<_>>>()?;
+    let divisor = integers
+        .iter()
+        .try_fold(0, |acc, value| gcd(acc, *value))?
+        .max(1);
+    let sign = match integers.iter().find(|value| **value != 0) {
+        Some(value) if *value < 0 => -1i64,
+        _ => 1,
+    };
+
+    integers
+        .into_iter()
+        .map(|value| {
+            let value = checked(sign.checked_mul(value / divisor))?;
+            i32::try_from(value).map_err(|_| Overflow)
+        })
+        .collect()
+}
@@ -1,21 +1,27 @@
+use std::rc::Rc;
+
 use crate::interpreter::variable::Unit;
 
 use crate::interpreter::scope::TowerScope;
+use crate::render::Renderer;
 
-pub enum Element<'a> {
-    String(&'a str),
-    Expr(Unit<'a>),
+pub enum Element {
+    String(Rc<str>),
+    Expr(Unit),
+    /// Unit rewritten in the basis of a `%[...]`, whose factors are printed as they are
+    Basis(Unit),
 }
-impl<'a> Element<'a> {
-    pub fn println(&self, scopes: &TowerScope<'a>) {
+impl Element {
+    pub fn render(&self, scopes: &TowerScope, renderer: Renderer) -> String {
         match self {
-            Self::String(string) => println!("{}", string),
-            Self::Expr(unit) => unit.println(scopes),
+            Self::String(string) => string.to_string(),
+            Self::Expr(unit) => unit.render(scopes, renderer),
+            Self::Basis(unit) => unit.render_in_basis(scopes, renderer),
         }
     }
 }
 
-// impl<'a> fmt::Display for Element<'a> {
+// impl fmt::Display for Element {
 //     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 //         match self {
 //             Self::String(string) => f.write_str(string),
@@ -1,75 +1,428 @@
+mod algebra;
 mod element;
+mod order;
+mod output;
+mod pi;
+mod product;
+mod resolve;
 mod scope;
+mod search;
+mod suggest;
 mod variable;
 
+pub use product::Product;
+pub use resolve::Program;
+pub use variable::{Axiom, Unit, Variable};
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::Instant;
+
 use element::Element;
+use order::Sorted;
+use output::Output;
+use resolve::{Declaration, IRExpr, IRStmt, RElement, RExpr, RStmt, Resolver};
 use scope::TowerScope;
-use variable::Unit;
 
 use crate::error::{DigifyError, ErrorKind, Result};
-use crate::parser::Element as AstElement;
-use crate::parser::{IExpr, IStmt, Stmt};
+use crate::limits::Limits;
+use crate::parser::{DigifyParser, IStmt, Span, Stmt};
+use crate::render::Renderer;
+use crate::source::{FileId, SourceMap};
+use crate::symbol::Symbol;
 
 #[derive(Debug, Default)]
 pub struct Interpreter<'a> {
-    scopes: TowerScope<'a>,
+    scopes: TowerScope,
+    renderer: Renderer,
+    output: Output<'a>,
+    sources: SourceMap,
+    limits: Limits,
+    /// Statements evaluated since the limits were set
+    statements: usize,
+    deadline: Option<Instant>,
+    sort_definitions: bool,
+    /// Variables whose declaration failed, the statements using them are skipped
+    poisoned: HashSet<Symbol>,
+    /// File of the last expression given to [`Self::parse_unit`]
+    unit_file: Option<FileId>,
 }
 
 impl<'a> Interpreter<'a> {
-    pub fn eval(&mut self, stmt: IStmt<'a>) -> Result<'a, ()> {
+    /// Style used by the `print` statements without an `as` modifier
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    /// Destination of the `print`, `pi` and `search` statements instead of the standard output
+    pub fn set_output(&mut self, output: impl Write + Send + 'a) {
+        self.output = Output::new(output);
+    }
+
+    /// Bound the work of the next parsings and evaluations, the statement count and the time
+    /// budget start over from this call
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.statements = 0;
+        self.deadline = limits.deadline();
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Evaluate the top-level `define` and `let` of the next programs before their other
+    /// statements, each after the definitions it references wherever they are written
+    pub fn set_sort_definitions(&mut self, sort_definitions: bool) {
+        self.sort_definitions = sort_definitions;
+    }
+
+    /// Resolve the names of `program` against the variables declared so far, finding every
+    /// undeclared or redeclared name at once, see [`Program::errors`]. Its statements are
+    /// evaluated in the current scope by [`Self::run`], which must come before any other
+    /// evaluation.
+    ///
+    /// With [`Self::set_sort_definitions`], its definitions are sorted first and the
+    /// definitions depending on each other fail.
+    pub fn resolve(&self, program: IStmt) -> Program {
+        let stmts = match program.item {
+            Stmt::Block(stmts) => stmts,
+            _ => vec![program],
+        };
+
+        let mut resolver = Resolver::new(&self.scopes);
+        let stmts = if self.sort_definitions {
+            order::sort_definitions(stmts)
+                .into_iter()
+                .map(|stmt| match stmt {
+                    Sorted::Stmt(stmt) => resolver.stmt(stmt),
+                    Sorted::Cyclic(stmt, error) => resolver.cyclic(stmt, error),
+                })
+                .collect()
+        } else {
+            stmts.into_iter().map(|stmt| resolver.stmt(stmt)).collect()
+        };
+
+        Program { stmts }
+    }
+
+    /// Evaluate a program given by [`Self::resolve`], going on after a failing statement.
+    /// The variables it should have declared are poisoned: the statements using them are
+    /// skipped without an other error. Every error is returned in the order of evaluation,
+    /// the name errors of the program where their statements would have been evaluated.
+    ///
+    /// Only the errors of [`ErrorKind::stops_evaluation`] end the evaluation early.
+    pub fn run(&mut self, program: Program) -> std::result::Result<(), Vec<DigifyError>> {
+        let mut errors = Vec::new();
+        for stmt in program.stmts {
+            if let Err(error) = self.execute(stmt, &mut errors) {
+                errors.push(error);
+                break;
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Resolve and evaluate `stmt`, a block having its own scope. The evaluation goes on
+    /// after an error like with [`Self::run`], but only the first one is returned.
+    pub fn eval(&mut self, stmt: IStmt) -> Result<()> {
+        let stmt = Resolver::new(&self.scopes).stmt(stmt);
+
+        let mut errors = Vec::new();
+        self.execute(stmt, &mut errors)?;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(first_error(errors))
+        }
+    }
+
+    /// Evaluate `stmt`, the errors of its statements being pushed to `errors`.
+    /// Only an error stopping the evaluation is returned.
+    fn execute(&mut self, stmt: IRStmt, errors: &mut Vec<DigifyError>) -> Result<()> {
+        if let RStmt::Block(stmts) = stmt.item {
+            // The scope is left even on an error, the next evaluations resolve against it
+            self.scopes.enter_scope();
+            let result = stmts
+                .into_iter()
+                .try_for_each(|stmt| self.execute(stmt, errors));
+            self.scopes.exit_scope();
+            return result;
+        }
+
+        let declared = stmt.item.declared();
+        if let RStmt::Failed(failures, _) = stmt.item {
+            self.poisoned.extend(declared);
+            errors.extend(failures);
+            return Ok(());
+        }
+
+        let mut uses = Vec::new();
+        stmt.item.uses(&mut uses);
+        let result = if uses.iter().any(|symbol| self.poisoned.contains(symbol)) {
+            // Its error would only repeat the one of the poisoned variable
+            self.poisoned.extend(declared);
+            Ok(())
+        } else {
+            self.execute_stmt(stmt)
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind().stops_evaluation() => Err(error),
+            Err(error) => {
+                self.poisoned.extend(declared);
+                errors.push(error);
+                Ok(())
+            }
+        }
+    }
+
+    /// Evaluate a statement other than a block
+    fn execute_stmt(&mut self, stmt: IRStmt) -> Result<()> {
+        self.statements += 1;
+        self.limits.check_statements(self.statements, stmt.span)?;
+        self.check_time(stmt.span)?;
+
+        if let Some(symbol) = stmt.item.declared() {
+            // The symbol may be reused once the poisoned variable is out of scope
+            self.poisoned.remove(&symbol);
+        }
+
         match stmt.item {
-            Stmt::Definition(ident, symbole) => self.scopes.define(ident, symbole.item)?,
-            Stmt::Let(ident, expr) => self.scopes.insert(ident, self.eval_expr(expr)?)?,
-            Stmt::Assert(unit1, unit2) => {
+            RStmt::Definition(declaration, symbole) => self.scopes.define(declaration, symbole)?,
+            RStmt::Let(declaration, expr) => {
+                let span = expr.span;
+                let unit = self.eval_expr(expr)?;
+                // An overflowing definition is rejected here rather than where it is used
+                let simplified = unit.simplify(&self.scopes, span)?;
+                self.limits.check_unit(&simplified, span)?;
+                self.scopes.insert(declaration, unit)?;
+            }
+            RStmt::Assert(unit1, unit2) => {
+                let (span1, span2) = (unit1.span, unit2.span);
                 let unit1 = self.eval_expr(unit1)?;
                 let unit2 = self.eval_expr(unit2)?;
 
-                let cmp = unit1.eq(&unit2, &self.scopes);
+                let cmp = unit1.eq(&unit2, &self.scopes, stmt.span)?;
                 if !cmp {
-                    let kind = ErrorKind::AssertFail(
-                        unit1.to_string(&self.scopes),
-                        unit2.to_string(&self.scopes),
-                    );
-                    let span = stmt.span.clone();
+                    let failure = unit1.diff(&unit2, &self.scopes, stmt.span)?;
+                    let labels = [
+                        format!("this is {}", failure.expected),
+                        format!("this is {}", failure.actual),
+                    ];
+                    let kind = ErrorKind::AssertFail(Box::new(failure));
+                    let [label1, label2] = labels;
+                    let error = DigifyError::new(kind, stmt.span)
+                        .with_label(span1, label1)
+                        .with_label(span2, label2);
 
-                    return Err(DigifyError::new(kind, span));
+                    return Err(error);
+                }
+            }
+            RStmt::Print(element, renderer) => {
+                let renderer = renderer.unwrap_or(self.renderer);
+                let line = self.eval_element(element)?.render(&self.scopes, renderer);
+                self.println(&line, &stmt.span)?;
+            }
+            RStmt::Solve(unknown, lhs, rhs) => {
+                let unit = self.eval_solve(&unknown, lhs, rhs, &stmt.span)?;
+                self.limits.check_unit(&unit, stmt.span)?;
+                self.scopes.insert(unknown, unit)?;
+            }
+            RStmt::Pi(variables) => {
+                let groups = self.pi_groups_of(&variables)?;
+                if groups.is_empty() {
+                    self.println("No dimensionless group", &stmt.span)?;
+                }
+                for (i, group) in groups.iter().enumerate() {
+                    self.println(&format!("π{} = {}", i + 1, group), &stmt.span)?;
                 }
             }
-            Stmt::Print(element) => self.eval_element(element)?.println(&self.scopes),
-            Stmt::Block(stmts) => {
-                self.scopes.enter_scope();
-                for stmt in stmts {
-                    self.eval(stmt)?;
+            RStmt::Prefer(variables) => self.scopes.prefer(variables)?,
+            RStmt::Search(target, variables, bound) => {
+                let bound = bound.unwrap_or(search::DEFAULT_SEARCH_BOUND);
+                let products = self.search_of(target, &variables, bound)?;
+                if products.is_empty() {
+                    self.println("No combination found", &stmt.span)?;
+                }
+                for product in products {
+                    self.println(&product.to_string(), &stmt.span)?;
                 }
-                self.scopes.exit_scope();
+            }
+            RStmt::Block(_) | RStmt::Failed(..) => {
+                unreachable!("blocks and failed statements are evaluated by execute")
             }
         };
 
         Ok(())
     }
 
-    fn eval_expr(&self, expr: IExpr<'a>) -> Result<'a, Unit<'a>> {
-        let unit = Unit::from(expr.item, &self.scopes)?;
+    /// Error located at `span` once the time budget is spent
+    fn check_time(&self, span: Span) -> Result<()> {
+        self.limits.check_time(self.deadline, span)
+    }
 
-        let idents = unit.top().keys().chain(unit.bottom().keys());
+    fn println(&mut self, line: &str, span: &Span) -> Result<()> {
+        self.output.println(line).map_err(|error| {
+            let kind = ErrorKind::Output(error.to_string());
+            DigifyError::new(kind, *span)
+        })
+    }
 
-        for ident in idents {
-            if !self.scopes.contains(ident) {
-                let king = ErrorKind::VariableNotDeclared((*ident).to_owned());
-                let span = expr.span;
-                return Err(DigifyError::new(king, span));
-            }
+    /// Evaluate the statements of `program` directly in the current scope,
+    /// so that its variables stay reachable once it has been evaluated.
+    /// Only the first error is returned, see [`Self::resolve`] and [`Self::run`] for all of them.
+    pub fn load(&mut self, program: IStmt) -> Result<()> {
+        let program = self.resolve(program);
+        self.run(program).map_err(first_error)
+    }
+
+    /// Parse `input` as a program and evaluate it in the current scope, see [`Self::load`].
+    ///
+    /// The input stays in the [sources](Self::sources) to locate the errors and the
+    /// declarations of its variables. A long-running host can rather add it itself with
+    /// [`Self::sources_mut`], parse and [load](Self::load) it, and then
+    /// [remove](SourceMap::remove) it once it does not need them.
+    pub fn eval_str(&mut self, input: impl Into<String>) -> Result<()> {
+        let file = self.sources.add("<input>", input);
+        // The parsing spends the time budget of the interpreter
+        let program = DigifyParser::parse_program(&self.sources, file, &self.limits, self.deadline)
+            .map_err(first_error)?;
+        self.load(program)
+    }
+
+    /// Files parsed by the interpreter, to locate the spans of its errors
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    pub fn sources_mut(&mut self) -> &mut SourceMap {
+        &mut self.sources
+    }
+
+    /// Variable named `ident` in the innermost scope declaring it
+    pub fn lookup(&self, ident: &str) -> Option<&Variable> {
+        self.scopes.lookup(ident.into()).map(|variable| &variable.item)
+    }
+
+    /// `unit` with its variables replaced by axioms, `None` if an exponent overflows
+    pub fn simplify(&self, unit: &Unit) -> Option<Unit> {
+        self.scopes.simplify(unit)
+    }
+
+    /// Unit of a standalone expression like `kg m s-2`, with the variables declared so far.
+    ///
+    /// The expression is kept in the [sources](Self::sources) until the next call only, so
+    /// that parsing many units does not grow them: an error can be reported until then.
+    pub fn parse_unit(&mut self, input: impl Into<String>) -> Result<Unit> {
+        if let Some(file) = self.unit_file.take() {
+            self.sources.remove(file);
         }
+        let file = self.sources.add("<unit>", input);
+        self.unit_file = Some(file);
+        let expr = DigifyParser::parse_unit(&self.sources, file, &self.limits)?;
+
+        let mut resolver = self.resolver();
+        let expr = resolver.expr(expr);
+        let expr = resolver.finish(expr).map_err(first_error)?;
+        self.eval_expr(expr)
+    }
+
+    /// Text of `unit` in the style of the interpreter
+    pub fn render(&self, unit: &Unit) -> String {
+        unit.render(&self.scopes, self.renderer)
+    }
+
+    /// Resolver of names against the variables declared so far
+    fn resolver(&self) -> Resolver<'_> {
+        Resolver::new(&self.scopes)
+    }
+
+    fn eval_expr(&self, expr: IRExpr) -> Result<Unit> {
+        let span = expr.span;
+        let unit = Unit::from(expr, &self.scopes)?;
+        self.limits.check_unit(&unit, span)?;
 
         Ok(unit)
     }
 
-    fn eval_element(&self, element: AstElement<'a>) -> Result<'a, Element<'a>> {
+    /// Solve `lhs == rhs` for `unknown`, the result only contains axioms
+    fn eval_solve(
+        &mut self,
+        unknown: &Declaration,
+        lhs: IRExpr,
+        rhs: IRExpr,
+        span: &Span,
+    ) -> Result<Unit> {
+        // The unknown lives as an axiom in a temporary scope, so that it survives
+        // the simplification of both sides
+        let name = unknown.name.clone();
+        self.scopes.enter_scope();
+        let residual = self
+            .scopes
+            .define(unknown.clone(), name.item)
+            .and_then(|_| {
+                let (lhs_span, rhs_span) = (lhs.span, rhs.span);
+                let lhs = self.eval_expr(lhs)?.simplify(&self.scopes, lhs_span)?;
+                let rhs = self.eval_expr(rhs)?.simplify(&self.scopes, rhs_span)?;
+
+                lhs.checked_div(rhs).ok_or_else(|| variable::overflow(*span))
+            });
+        self.scopes.exit_scope();
+        let residual = residual?;
+
+        let no_solution = |reason: String| {
+            let kind = ErrorKind::NoSolution(name.as_str().to_owned(), reason);
+            DigifyError::new(kind, *span)
+        };
+
+        // residual = unknown^power * rest = 1, so unknown = rest^(-1 / power)
+        let power = residual.exponent(unknown.symbol);
+        if power == 0 {
+            let reason = format!("{} cancels out of the equation", name.as_str());
+            return Err(no_solution(reason));
+        }
+
+        let mut exponents = Vec::new();
+        for (ident, exponent) in residual.exponents() {
+            if ident == unknown.symbol {
+                continue;
+            }
+            // Only `i32::MIN / -1` overflows, with a remainder of 0
+            if exponent.checked_rem(power).is_some_and(|rest| rest != 0) {
+                let reason = format!("the exponent of {} is not a multiple of {}", ident, power);
+                return Err(no_solution(reason));
+            }
+            let exponent = exponent
+                .checked_div(power)
+                .and_then(i32::checked_neg)
+                .ok_or_else(|| variable::overflow(*span))?;
+            exponents.push((ident, exponent));
+        }
+
+        Unit::checked_from_exponents(exponents).ok_or_else(|| variable::overflow(*span))
+    }
+
+    fn eval_element(&self, element: RElement) -> Result<Element> {
         let element = match element {
-            AstElement::Expr(expr) => Element::Expr(self.eval_expr(expr)?),
-            AstElement::String(string) => Element::String(string.item),
+            RElement::Expr(expr) if matches!(expr.item, RExpr::Simplify(_, Some(_))) => {
+                Element::Basis(self.eval_expr(expr)?)
+            }
+            RElement::Expr(expr) => Element::Expr(self.eval_expr(expr)?),
+            RElement::String(string) => Element::String(string),
         };
 
         Ok(element)
     }
 }
+
+/// First error of a resolution, for the callers expecting a single error
+fn first_error(mut errors: Vec<DigifyError>) -> DigifyError {
+    errors.swap_remove(0)
+}
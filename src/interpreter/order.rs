@@ -0,0 +1,187 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{DigifyError, ErrorKind};
+use crate::parser::{Expr, IExpr, IStmt, Istr, Stmt};
+use crate::symbol::Symbol;
+
+/// Statement of a sorted program
+pub enum Sorted {
+    Stmt(IStmt),
+    /// Definition of a cycle, which fails without being evaluated. The first definition of
+    /// the cycle carries its error.
+    Cyclic(IStmt, Option<DigifyError>),
+}
+
+/// `stmts` with their `define`, `let` and `solve` first, each after the definitions it
+/// references, then the other statements in their order.
+/// Definitions referencing each other are reported with the names around each cycle, and
+/// placed once the definitions they use outside of it.
+pub fn sort_definitions(stmts: Vec<IStmt>) -> Vec<Sorted> {
+    let (definitions, others): (Vec<_>, Vec<_>) = stmts
+        .into_iter()
+        .partition(|stmt| {
+            matches!(
+                stmt.item,
+                Stmt::Definition(..) | Stmt::Let(..) | Stmt::Solve(..)
+            )
+        });
+
+    // A name defined twice is left to the resolution, which reports it
+    let mut indices = HashMap::new();
+    for (i, definition) in definitions.iter().enumerate() {
+        indices.entry(name(definition).item).or_insert(i);
+    }
+
+    // dependencies[i] are the definitions used by the i-th one, dependents the other way
+    let mut dependencies = vec![BTreeSet::new(); definitions.len()];
+    let mut dependents = vec![Vec::new(); definitions.len()];
+    for (i, definition) in definitions.iter().enumerate() {
+        let mut names = Vec::new();
+        match &definition.item {
+            Stmt::Let(_, expr) => references(expr, &mut names),
+            // The unknown is declared while both sides are evaluated, it is not a dependency
+            Stmt::Solve(unknown, lhs, rhs) => {
+                references(lhs, &mut names);
+                references(rhs, &mut names);
+                names.retain(|name| *name != unknown.item);
+            }
+            _ => {}
+        }
+        dependencies[i].extend(names.iter().filter_map(|name| indices.get(name).copied()));
+        for &dependency in &dependencies[i] {
+            dependents[dependency].push(i);
+        }
+    }
+
+    // Kahn's algorithm, the ready definition written first going first
+    let mut pending = dependencies.iter().map(BTreeSet::len).collect::<Vec<_>>();
+    let mut placed = vec![false; definitions.len()];
+    let mut ready = (0..definitions.len())
+        .filter(|&i| pending[i] == 0)
+        .collect::<BTreeSet<_>>();
+    let mut order = Vec::with_capacity(definitions.len());
+    let mut errors = HashMap::new();
+
+    loop {
+        while let Some(i) = ready.pop_first() {
+            placed[i] = true;
+            order.push(i);
+            release(i, &dependents, &placed, &mut pending, &mut ready);
+        }
+
+        // Every definition left waits on an other one left, so following them loops
+        let Some(start) = (0..definitions.len()).find(|&i| !placed[i]) else {
+            break;
+        };
+        let cycle = find_cycle(start, &dependencies, &placed);
+        for &i in &cycle {
+            placed[i] = true;
+            order.push(i);
+        }
+        // The definitions waiting only on this cycle may still be in an other one
+        for &i in &cycle {
+            release(i, &dependents, &placed, &mut pending, &mut ready);
+        }
+
+        let mut names = cycle
+            .iter()
+            .map(|&i| name(&definitions[i]).as_str().to_owned())
+            .collect::<Vec<_>>();
+        names.push(names[0].clone());
+        // Each definition after the first one is labelled with the one it uses
+        let labels = cycle[1..]
+            .iter()
+            .zip(&names[2..])
+            .map(|(&i, next)| (name(&definitions[i]).span, format!("uses {}", next)))
+            .collect::<Vec<_>>();
+        let kind = ErrorKind::CyclicDefinitions(names);
+        let error = DigifyError::new(kind, name(&definitions[cycle[0]]).span);
+        let error = labels
+            .into_iter()
+            .fold(error, |error, (span, message)| error.with_label(span, message));
+        for &i in &cycle {
+            errors.insert(i, None);
+        }
+        errors.insert(cycle[0], Some(error));
+    }
+
+    let mut definitions = definitions.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .filter_map(|i| {
+            let definition = definitions[i].take()?;
+            Some(match errors.remove(&i) {
+                Some(error) => Sorted::Cyclic(definition, error),
+                None => Sorted::Stmt(definition),
+            })
+        })
+        .chain(others.into_iter().map(Sorted::Stmt))
+        .collect()
+}
+
+/// Name declared by a `define`, a `let` or a `solve`
+fn name(stmt: &IStmt) -> &Istr {
+    match &stmt.item {
+        Stmt::Definition(ident, _) | Stmt::Let(ident, _) | Stmt::Solve(ident, _, _) => ident,
+        _ => unreachable!("only definitions are sorted"),
+    }
+}
+
+/// Count the definition `done` as placed for the definitions waiting on it
+fn release(
+    done: usize,
+    dependents: &[Vec<usize>],
+    placed: &[bool],
+    pending: &mut [usize],
+    ready: &mut BTreeSet<usize>,
+) {
+    for &dependent in &dependents[done] {
+        if placed[dependent] {
+            continue;
+        }
+        pending[dependent] -= 1;
+        if pending[dependent] == 0 {
+            ready.insert(dependent);
+        }
+    }
+}
+
+/// Cycle reached from `start` by following the dependencies not placed yet,
+/// beginning with its definition written first
+fn find_cycle(start: usize, dependencies: &[BTreeSet<usize>], placed: &[bool]) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut positions = HashMap::new();
+    let mut current = start;
+
+    while !positions.contains_key(&current) {
+        positions.insert(current, path.len());
+        path.push(current);
+        current = *dependencies[current]
+            .iter()
+            .find(|&&dependency| !placed[dependency])
+            .expect("a definition left waits on an other one left");
+    }
+
+    let mut cycle = path.split_off(positions[&current]);
+    let first = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+    cycle.rotate_left(first);
+
+    cycle
+}
+
+/// Names used by `expr`, including the basis of its simplifications
+fn references(expr: &IExpr, names: &mut Vec<Symbol>) {
+    match &expr.item {
+        Expr::Ident(ident) => names.push(ident.item),
+        Expr::Mul(expr1, expr2) | Expr::Div(expr1, expr2) => {
+            references(expr1, names);
+            references(expr2, names);
+        }
+        Expr::Power(expr, _) => references(expr, names),
+        Expr::Simplify(expr, basis) => {
+            references(expr, names);
+            names.extend(basis.iter().flatten().map(|ident| ident.item));
+        }
+        Expr::None => {}
+    }
+}
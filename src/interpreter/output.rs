@@ -0,0 +1,27 @@
+use std::fmt;
+use std::io::{self, Write};
+
+/// Destination of the `print` statements, the standard output by default
+pub struct Output<'a>(Box<dyn Write + Send + 'a>);
+
+impl<'a> Output<'a> {
+    pub fn new(output: impl Write + Send + 'a) -> Self {
+        Self(Box::new(output))
+    }
+
+    pub fn println(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.0, "{}", line)
+    }
+}
+
+impl Default for Output<'_> {
+    fn default() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl fmt::Debug for Output<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Output")
+    }
+}
@@ -0,0 +1,39 @@
+use crate::error::Result;
+use crate::parser::{Istr, Span};
+
+use super::algebra;
+use super::product::{self, Product};
+use super::resolve::IBinding;
+use super::{first_error, variable, Interpreter};
+
+impl<'a> Interpreter<'a> {
+    /// Basis of the independent dimensionless products of `variables` (Buckingham π theorem)
+    pub fn pi_groups(&self, variables: &[Istr]) -> Result<Vec<Product>> {
+        let mut resolver = self.resolver();
+        let variables = resolver.idents(variables.to_vec());
+        let variables = resolver.finish(variables).map_err(first_error)?;
+
+        self.pi_groups_of(&variables)
+    }
+
+    pub(super) fn pi_groups_of(&self, variables: &[IBinding]) -> Result<Vec<Product>> {
+        let units = self.simplified(variables)?;
+        let axioms = product::axioms(&units);
+        let matrix = product::dimension_matrix(&axioms, &units);
+
+        // The overflow is located on the whole list of variables
+        let span = match (variables.first(), variables.last()) {
+            (Some(first), Some(last)) => {
+                Span::new(first.span.file(), first.span.start(), last.span.end())
+            }
+            _ => Span::default(),
+        };
+        let groups = algebra::null_space(&matrix, variables.len())
+            .map_err(|_| variable::overflow(span))?
+            .into_iter()
+            .map(|powers| Product::new(variables, &powers))
+            .collect();
+
+        Ok(groups)
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::symbol::Symbol;
+
+use super::resolve::IBinding;
+use super::variable::Unit;
+use super::Interpreter;
+
+/// Product of variables, each paired with its non zero exponent
+#[derive(Debug)]
+pub struct Product {
+    factors: Vec<(Symbol, i32)>,
+}
+
+impl Product {
+    pub(crate) fn new(variables: &[IBinding], powers: &[i32]) -> Self {
+        let factors = variables
+            .iter()
+            .zip(powers)
+            .filter(|(_, power)| **power != 0)
+            .map(|(variable, power)| (variable.item.symbol, *power))
+            .collect();
+
+        Self { factors }
+    }
+}
+
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let factors = self
+            .factors
+            .iter()
+            .map(|(ident, power)| match power {
+                1 => ident.to_string(),
+                _ => format!("{}{}", ident, power),
+            })
+            .collect::<Vec<_>>();
+
+        f.write_str(&factors.join(" "))
+    }
+}
+
+/// Sorted axioms appearing in at least one of `units`
+pub fn axioms(units: &[Unit]) -> Vec<Symbol> {
+    let mut axioms = units
+        .iter()
+        .flat_map(|unit| unit.exponents())
+        .map(|(axiom, _)| axiom)
+        .collect::<Vec<_>>();
+    axioms.sort_unstable();
+    axioms.dedup();
+
+    axioms
+}
+
+/// Exponents of `units` with one row per axiom and one column per unit
+pub fn dimension_matrix(axioms: &[Symbol], units: &[Unit]) -> Vec<Vec<i32>> {
+    axioms
+        .iter()
+        .map(|axiom| units.iter().map(|unit| unit.exponent(*axiom)).collect())
+        .collect()
+}
+
+impl<'a> Interpreter<'a> {
+    /// Units of `variables` simplified down to axioms
+    pub(super) fn simplified(&self, variables: &[IBinding]) -> Result<Vec<Unit>> {
+        variables
+            .iter()
+            .map(|variable| self.scopes.simplified(variable))
+            .collect()
+    }
+}
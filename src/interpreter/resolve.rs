@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::error::{DigifyError, ErrorKind};
+use crate::parser::{Element, Expr, IExpr, IStmt, Istr, Item, Stmt};
+use crate::render::Renderer;
+use crate::symbol::Symbol;
+
+use super::scope::TowerScope;
+use super::suggest;
+use super::variable::Variable;
+
+/// Variable a name refers to: the symbol standing for it in the units, which no other
+/// visible variable has, and the index of the scope declaring it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub symbol: Symbol,
+    pub scope: usize,
+}
+
+pub type IBinding = Item<Binding>;
+pub type IRStmt = Item<RStmt>;
+pub type IRExpr = Item<RExpr>;
+
+/// Statement whose names are resolved to their bindings
+#[derive(Debug)]
+pub enum RStmt {
+    Definition(Declaration, Symbol),
+    Let(Declaration, IRExpr),
+    Assert(IRExpr, IRExpr),
+    Print(RElement, Option<Renderer>),
+    /// The unknown is declared as an axiom in a scope of its own while both sides are evaluated
+    Solve(Declaration, IRExpr, IRExpr),
+    Pi(Vec<IBinding>),
+    Search(IRExpr, Vec<IBinding>, Option<u32>),
+    Prefer(Vec<IBinding>),
+    Block(Vec<IRStmt>),
+    /// Statement whose names could not be resolved, with its errors. It fails where it
+    /// would have been evaluated, poisoning the variable it declares.
+    Failed(Vec<DigifyError>, Option<Declaration>),
+}
+
+impl RStmt {
+    /// Symbol of the variable declared by the statement in its scope
+    pub fn declared(&self) -> Option<Symbol> {
+        match self {
+            Self::Definition(declaration, _)
+            | Self::Let(declaration, _)
+            | Self::Solve(declaration, _, _)
+            | Self::Failed(_, Some(declaration)) => Some(declaration.symbol),
+            _ => None,
+        }
+    }
+
+    /// Symbols of the variables used by the statement
+    pub fn uses(&self, symbols: &mut Vec<Symbol>) {
+        let idents = |idents: &[IBinding], symbols: &mut Vec<Symbol>| {
+            symbols.extend(idents.iter().map(|binding| binding.item.symbol))
+        };
+
+        match self {
+            Self::Definition(..) | Self::Failed(..) => {}
+            Self::Let(_, expr) | Self::Print(RElement::Expr(expr), _) => expr.item.uses(symbols),
+            Self::Print(RElement::String(_), _) => {}
+            Self::Assert(expr1, expr2) | Self::Solve(_, expr1, expr2) => {
+                expr1.item.uses(symbols);
+                expr2.item.uses(symbols);
+            }
+            Self::Pi(variables) | Self::Prefer(variables) => idents(variables, symbols),
+            Self::Search(target, variables, _) => {
+                target.item.uses(symbols);
+                idents(variables, symbols);
+            }
+            Self::Block(stmts) => stmts.iter().for_each(|stmt| stmt.item.uses(symbols)),
+        }
+    }
+}
+
+/// Expression whose idents are resolved to their bindings
+#[derive(Debug)]
+pub enum RExpr {
+    Variable(IBinding),
+    Mul(Box<IRExpr>, Box<IRExpr>),
+    Div(Box<IRExpr>, Box<IRExpr>),
+    Power(Box<IRExpr>, i32),
+    Simplify(Box<IRExpr>, Option<Vec<IBinding>>),
+    None,
+}
+
+impl RExpr {
+    /// Symbols of the variables used by the expression, including the basis of its
+    /// simplifications
+    pub fn uses(&self, symbols: &mut Vec<Symbol>) {
+        match self {
+            Self::Variable(binding) => symbols.push(binding.item.symbol),
+            Self::Mul(expr1, expr2) | Self::Div(expr1, expr2) => {
+                expr1.item.uses(symbols);
+                expr2.item.uses(symbols);
+            }
+            Self::Power(expr, _) => expr.item.uses(symbols),
+            Self::Simplify(expr, basis) => {
+                expr.item.uses(symbols);
+                symbols.extend(basis.iter().flatten().map(|binding| binding.item.symbol));
+            }
+            Self::None => {}
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RElement {
+    String(Rc<str>),
+    Expr(IRExpr),
+}
+
+/// Variable declared in the innermost scope under `name`
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub name: Istr,
+    pub symbol: Symbol,
+}
+
+/// Program resolved against the variables of an [`Interpreter`](crate::Interpreter),
+/// see [`Interpreter::resolve`](crate::Interpreter::resolve)
+#[derive(Debug)]
+pub struct Program {
+    pub(super) stmts: Vec<IRStmt>,
+}
+
+impl Program {
+    /// Undeclared and redeclared names of the program, and the cycles between its sorted
+    /// definitions. [`Interpreter::run`](crate::Interpreter::run) reports them again where
+    /// their statements would have been evaluated.
+    pub fn errors(&self) -> Vec<&DigifyError> {
+        fn collect<'p>(stmts: &'p [IRStmt], errors: &mut Vec<&'p DigifyError>) {
+            for stmt in stmts {
+                match &stmt.item {
+                    RStmt::Failed(failures, _) => errors.extend(failures),
+                    RStmt::Block(stmts) => collect(stmts, errors),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut errors = Vec::new();
+        collect(&self.stmts, &mut errors);
+        errors
+    }
+}
+
+/// Lowering of the AST to the resolved IR, on top of the variables already in `scopes`.
+///
+/// The scopes are followed statically like the evaluation would, so every undeclared or
+/// redeclared name is found before anything is evaluated.
+pub struct Resolver<'s> {
+    scopes: &'s TowerScope,
+    /// Symbols of the names declared by the resolved statements, located at their
+    /// declaration, the first map extending the innermost scope of `scopes`
+    declared: Vec<HashMap<Symbol, Item<Symbol>>>,
+    /// Symbol of the axiom of each variable declared by a resolved `define`
+    symboles: HashMap<Symbol, Symbol>,
+    errors: Vec<DigifyError>,
+}
+
+impl<'s> Resolver<'s> {
+    pub fn new(scopes: &'s TowerScope) -> Self {
+        Self {
+            scopes,
+            declared: vec![HashMap::new()],
+            symboles: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// `value` if no error was found while resolving it
+    pub fn finish<T>(self, value: T) -> Result<T, Vec<DigifyError>> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    /// Statement resolved, or failed with the errors of its names
+    pub fn stmt(&mut self, stmt: IStmt) -> IRStmt {
+        let errors = self.errors.len();
+        let mut resolved = self.resolve_stmt(stmt);
+        if self.errors.len() > errors {
+            let declaration = match resolved.item {
+                RStmt::Definition(declaration, _)
+                | RStmt::Let(declaration, _)
+                | RStmt::Solve(declaration, _, _) => Some(declaration),
+                _ => None,
+            };
+            resolved.item = RStmt::Failed(self.errors.split_off(errors), declaration);
+        }
+
+        resolved
+    }
+
+    /// Definition of a cycle, failing with `error`. Its name is declared all the same, so
+    /// that the statements using it are skipped rather than reported.
+    pub fn cyclic(&mut self, stmt: IStmt, error: Option<DigifyError>) -> IRStmt {
+        let name = match stmt.item {
+            Stmt::Definition(name, _) | Stmt::Let(name, _) | Stmt::Solve(name, _, _) => name,
+            _ => unreachable!("only definitions are in cycles"),
+        };
+
+        let errors = self.errors.len();
+        let declaration = self.declare(name);
+        let errors = self.errors.split_off(errors).into_iter().chain(error).collect();
+        Item::new(RStmt::Failed(errors, Some(declaration)), stmt.span)
+    }
+
+    fn resolve_stmt(&mut self, stmt: IStmt) -> IRStmt {
+        let resolved = match stmt.item {
+            Stmt::Definition(ident, symbole) => {
+                let declaration = self.declare(ident);
+                self.symboles.insert(declaration.symbol, symbole.item);
+                RStmt::Definition(declaration, symbole.item)
+            }
+            Stmt::Let(ident, expr) => {
+                // The expression only sees the variables declared before
+                let expr = self.expr(expr);
+                RStmt::Let(self.declare(ident), expr)
+            }
+            Stmt::Assert(expr1, expr2) => RStmt::Assert(self.expr(expr1), self.expr(expr2)),
+            Stmt::Print(Element::String(string), renderer) => {
+                RStmt::Print(RElement::String(string.item), renderer)
+            }
+            Stmt::Print(Element::Expr(expr), renderer) => {
+                RStmt::Print(RElement::Expr(self.expr(expr)), renderer)
+            }
+            Stmt::Solve(unknown, lhs, rhs) => {
+                let symbol = self.symbol_for(unknown.item);
+
+                let declaration = Item::new(symbol, unknown.span);
+                self.declared.push(HashMap::from([(unknown.item, declaration)]));
+                let lhs = self.expr(lhs);
+                let rhs = self.expr(rhs);
+                self.declared.pop();
+
+                RStmt::Solve(self.declare_as(unknown, symbol), lhs, rhs)
+            }
+            Stmt::Pi(idents) => RStmt::Pi(self.idents(idents)),
+            Stmt::Search(target, idents, bound) => {
+                RStmt::Search(self.expr(target), self.idents(idents), bound)
+            }
+            Stmt::Prefer(idents) => RStmt::Prefer(self.idents(idents)),
+            Stmt::Block(stmts) => {
+                self.declared.push(HashMap::new());
+                let stmts = stmts.into_iter().map(|stmt| self.stmt(stmt)).collect();
+                self.declared.pop();
+
+                RStmt::Block(stmts)
+            }
+        };
+
+        Item::new(resolved, stmt.span)
+    }
+
+    pub fn expr(&mut self, expr: IExpr) -> IRExpr {
+        let resolved = match expr.item {
+            Expr::Ident(ident) => RExpr::Variable(self.ident(ident)),
+            Expr::Mul(expr1, expr2) => {
+                RExpr::Mul(Box::new(self.expr(*expr1)), Box::new(self.expr(*expr2)))
+            }
+            Expr::Div(expr1, expr2) => {
+                RExpr::Div(Box::new(self.expr(*expr1)), Box::new(self.expr(*expr2)))
+            }
+            Expr::Power(expr, number) => RExpr::Power(Box::new(self.expr(*expr)), number),
+            Expr::Simplify(expr, basis) => {
+                let expr = self.expr(*expr);
+                RExpr::Simplify(Box::new(expr), basis.map(|basis| self.idents(basis)))
+            }
+            Expr::None => RExpr::None,
+        };
+
+        Item::new(resolved, expr.span)
+    }
+
+    /// Bindings of a list of names, each of which may only appear once
+    pub fn idents(&mut self, idents: Vec<Istr>) -> Vec<IBinding> {
+        let mut first = HashMap::new();
+        idents
+            .into_iter()
+            .map(|ident| {
+                if let Some(previous) = first.get(&ident.item) {
+                    let kind = ErrorKind::RepeatedVariable(ident.as_str().to_owned());
+                    let error = DigifyError::new(kind, ident.span);
+                    self.errors.push(error.with_label(*previous, "first given here"));
+                } else {
+                    first.insert(ident.item, ident.span);
+                }
+
+                self.ident(ident)
+            })
+            .collect()
+    }
+
+    /// Binding of a visible variable, an error suggesting the close names is recorded if
+    /// there is none
+    fn ident(&mut self, ident: Istr) -> IBinding {
+        let binding = self.lookup(ident.item).unwrap_or_else(|| {
+            let kind = ErrorKind::VariableNotDeclared(ident.as_str().to_owned());
+            let suggestions = suggest::suggestions(ident.as_str(), self.visible());
+            let error = DigifyError::new(kind, ident.span).with_suggestions(suggestions);
+            self.errors.push(error);
+
+            Binding {
+                symbol: ident.item,
+                scope: 0,
+            }
+        });
+
+        Item::new(binding, ident.span)
+    }
+
+    fn lookup(&self, name: Symbol) -> Option<Binding> {
+        let innermost = self.scopes.depth();
+
+        self.declared
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(level, names)| {
+                let symbol = names.get(&name)?.item;
+                Some(Binding {
+                    symbol,
+                    scope: innermost + level,
+                })
+            })
+            .or_else(|| self.scopes.resolve(name))
+    }
+
+    /// Visible names with the symbol of their axiom, if any
+    fn visible(&self) -> Vec<(Symbol, Option<Symbol>)> {
+        let declared = self.declared.iter().flat_map(|names| {
+            names
+                .iter()
+                .map(|(name, symbol)| (*name, self.symboles.get(&symbol.item).copied()))
+        });
+        let existing = self.scopes.visible().map(|(name, variable)| match variable.item() {
+            Variable::Axiom(axiom) => (name, Some(axiom.symbole())),
+            Variable::Unit(_) => (name, None),
+        });
+
+        declared.chain(existing).collect()
+    }
+
+    /// Symbol of a new variable named `name`, the name itself unless it shadows an other one
+    fn symbol_for(&self, name: Symbol) -> Symbol {
+        match self.lookup(name) {
+            Some(_) => Symbol::fresh(name.as_str()),
+            None => name,
+        }
+    }
+
+    fn declare(&mut self, name: Istr) -> Declaration {
+        let symbol = self.symbol_for(name.item);
+        self.declare_as(name, symbol)
+    }
+
+    fn declare_as(&mut self, name: Istr, symbol: Symbol) -> Declaration {
+        // The first map shares its scope with the variables already declared
+        let outer = (self.declared.len() == 1 && self.scopes.declares(name.item))
+            .then(|| self.scopes.lookup(name.item))
+            .flatten()
+            .map(|variable| variable.span);
+        let names = self.declared.last_mut().unwrap();
+
+        // The name keeps its previous variable, the failed declaration only poisons its own
+        // fresh symbol
+        if let Some(previous) = names.get(&name.item).map(|previous| previous.span).or(outer) {
+            let kind = ErrorKind::VariableAlreadyDeclared(name.as_str().to_owned());
+            let error = DigifyError::new(kind, name.span);
+            self.errors
+                .push(error.with_label(previous, "previously declared here"));
+        } else {
+            names.insert(name.item, Item::new(symbol, name.span));
+        }
+
+        Declaration { name, symbol }
+    }
+}
@@ -1,124 +1,249 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 
-// use color_eyre::eyre::{eyre, OptionExt};
-
 use crate::error::{DigifyError, ErrorKind, Result};
 use crate::interpreter::variable::{Unit, Variable};
-use crate::parser::{Istr, Item};
+use crate::parser::Item;
+use crate::symbol::Symbol;
 
+use super::resolve::{Binding, Declaration, IBinding};
 use super::variable::{Axiom, IVariable};
 
-#[derive(Debug, Default)]
-pub struct TowerScope<'a> {
-    scopes: Vec<Scope<'a>>,
+#[derive(Debug)]
+pub struct TowerScope {
+    scopes: Vec<Scope>,
+    /// Variables reduced to axioms, with the depth of the innermost scope they depend on
+    simplified: RefCell<HashMap<Symbol, (Unit, usize)>>,
 }
 
 #[derive(Debug, Default)]
-pub struct Scope<'a> {
-    variables: HashMap<&'a str, IVariable<'a>>,
+pub struct Scope {
+    /// Variables by the symbol of their binding
+    variables: HashMap<Symbol, IVariable>,
+    /// Symbol of the variable declared under each name
+    names: HashMap<Symbol, Symbol>,
+    preferred: Vec<Symbol>,
 }
 
-impl<'a> TowerScope<'a> {
-    pub fn define(&mut self, ident: Istr<'a>, symbole: &'a str) -> Result<'a, ()> {
-        self.scopes.last_mut().unwrap().define(ident, symbole)
+impl TowerScope {
+    pub fn define(&mut self, declaration: Declaration, symbole: Symbol) -> Result<()> {
+        self.innermost().define(declaration, symbole)
+    }
+
+    pub fn insert(&mut self, declaration: Declaration, unit: Unit) -> Result<()> {
+        self.innermost().insert(declaration, unit)
+    }
+
+    fn innermost(&mut self) -> &mut Scope {
+        self.scopes.last_mut().unwrap()
+    }
+
+    pub fn prefer(&mut self, bindings: Vec<IBinding>) -> Result<()> {
+        for binding in bindings {
+            self.get(&binding)?;
+            self.innermost().preferred.push(binding.item.symbol);
+        }
+        Ok(())
     }
 
-    pub fn insert(&mut self, ident: Istr<'a>, unit: Unit<'a>) -> Result<'a, ()> {
-        self.scopes.last_mut().unwrap().insert(ident, unit)
+    /// Preferred display units of every visible scope
+    pub fn preferred(&self) -> Vec<Symbol> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.preferred.iter().copied())
+            .collect()
     }
 
-    pub fn contains(&self, ident: &'a str) -> bool {
-        self.scopes.iter().rev().any(|scope| scope.contains(ident))
+    /// Index of the innermost scope
+    pub fn depth(&self) -> usize {
+        self.scopes.len() - 1
     }
 
-    pub fn get(&self, ident: Istr<'a>) -> Result<'a, &IVariable<'a>> {
+    /// Binding of the visible variable named `name`
+    pub fn resolve(&self, name: Symbol) -> Option<Binding> {
         self.scopes
             .iter()
+            .enumerate()
             .rev()
-            .find_map(|scope| scope.get(&ident).ok())
+            .find_map(|(scope, variables)| {
+                let symbol = *variables.names.get(&name)?;
+                Some(Binding { symbol, scope })
+            })
+    }
+
+    /// Whether the innermost scope declares a variable named `name`
+    pub fn declares(&self, name: Symbol) -> bool {
+        self.scopes.last().unwrap().names.contains_key(&name)
+    }
+
+    /// Variable of a binding, which the resolver made sure exists
+    pub fn get(&self, binding: &IBinding) -> Result<&IVariable> {
+        self.scopes
+            .get(binding.item.scope)
+            .and_then(|scope| scope.get_existing(binding.item.symbol))
             .ok_or_else(|| {
-                let kind = ErrorKind::VariableNotDeclared(ident.as_str().to_owned());
-                let span = ident.span;
-                DigifyError::new(kind, span)
+                let name = binding.item.symbol.as_str().to_owned();
+                DigifyError::new(ErrorKind::VariableNotDeclared(name), binding.span)
             })
     }
 
-    pub fn get_existing(&self, ident: &str) -> Option<&IVariable<'a>> {
+    /// Name and variable of each visible variable, the innermost first
+    pub fn visible(&self) -> impl Iterator<Item = (Symbol, &IVariable)> + '_ {
+        self.scopes.iter().rev().flat_map(|scope| {
+            scope
+                .names
+                .iter()
+                .filter_map(|(name, symbol)| Some((*name, scope.get_existing(*symbol)?)))
+        })
+    }
+
+    /// Visible variable named `name`
+    pub fn lookup(&self, name: Symbol) -> Option<&IVariable> {
+        let binding = self.resolve(name)?;
+        self.scopes[binding.scope].get_existing(binding.symbol)
+    }
+
+    pub fn get_existing(&self, ident: Symbol) -> Option<&IVariable> {
         self.scopes
             .iter()
             .rev()
             .find_map(|scope| scope.get_existing(ident))
     }
 
-    pub fn enter_scope(&mut self) {
-        self.scopes.push(Scope::default())
+    /// Unit of the variable of `binding` reduced to axioms
+    pub fn simplified(&self, binding: &IBinding) -> Result<Unit> {
+        self.get(binding)?;
+        self.simplified_existing(binding.item.symbol)
+            .ok_or_else(|| DigifyError::new(ErrorKind::ExponentOverflow, binding.span))
     }
 
-    pub fn exit_scope(&mut self) {
-        self.scopes.pop();
+    /// Unit of the variable `ident` reduced to axioms, `None` if an exponent overflows
+    pub fn simplified_existing(&self, ident: Symbol) -> Option<Unit> {
+        Some(self.simplify_ident(ident)?.0)
     }
-}
 
-// impl<'a> Default for TowerScope<'a> {
-//     fn default() -> Self {
-//         Self {
-//             scopes: vec![Scope::default()],
-//         }
-//     }
-// }
-
-impl<'a> Scope<'a> {
-    fn define(&mut self, ident: Istr<'a>, symbole: &'a str) -> Result<'a, ()> {
-        if self.variables.contains_key(ident.as_str()) {
-            let kind = ErrorKind::VariableAlreadyDeclared(ident.as_str().to_owned());
-            let span = ident.span;
-
-            return Err(DigifyError::new(kind, span));
+    /// `unit` with its variables replaced by axioms, `None` if an exponent overflows
+    pub fn simplify(&self, unit: &Unit) -> Option<Unit> {
+        for (ident, _) in unit.iter() {
+            self.simplify_ident(ident)?;
         }
+        Some(self.reduce(unit)?.0)
+    }
 
-        let axiom = Axiom::new(ident.as_str(), symbole);
-        let axiom = Variable::Axiom(axiom);
-        let axiom = Item::new(axiom, ident.span);
+    /// Simplification of the visible variable `ident`, computed once per definition.
+    ///
+    /// The definitions it depends on are reduced first with a worklist instead of recursion,
+    /// so that chains like `let nT = T; let mT = nT; ...` can be arbitrarily long. They are
+    /// always declared before, so the worklist never meets a cycle.
+    fn simplify_ident(&self, ident: Symbol) -> Option<(Unit, usize)> {
+        let mut stack = vec![ident];
+
+        while let Some(&top) = stack.last() {
+            if self.simplified.borrow().contains_key(&top) {
+                stack.pop();
+                continue;
+            }
+
+            // An ident without variable is left as is
+            let simplified = match self.find(top) {
+                None => (Unit::from_exponents([(top, 1)]), self.depth()),
+                Some((depth, Variable::Axiom(axiom))) => (axiom.as_unit().clone(), depth),
+                Some((depth, Variable::Unit(unit))) => {
+                    let pending = unit
+                        .iter()
+                        .map(|(ident, _)| ident)
+                        .filter(|ident| !self.simplified.borrow().contains_key(ident))
+                        .collect::<Vec<_>>();
+                    if !pending.is_empty() {
+                        stack.extend(pending);
+                        continue;
+                    }
+
+                    let (unit, unit_depth) = self.reduce(unit)?;
+                    (unit, depth.max(unit_depth))
+                }
+            };
+
+            self.simplified.borrow_mut().insert(top, simplified);
+            stack.pop();
+        }
 
-        // ident.item is used instead of ident.as_str() because the borrow checker
-        // can not know that the ident.as_str() only borrow ident.item
-        self.variables.insert(ident.item, axiom);
-        Ok(())
+        Some(self.simplified.borrow()[&ident].clone())
     }
 
-    fn insert(&mut self, ident: Istr<'a>, unit: Unit<'a>) -> Result<'a, ()> {
-        if self.variables.contains_key(ident.as_str()) {
-            let kind = ErrorKind::VariableAlreadyDeclared(ident.as_str().to_owned());
-            let span = ident.span;
+    /// `unit` with its variables replaced by their known simplification
+    fn reduce(&self, unit: &Unit) -> Option<(Unit, usize)> {
+        let simplified = self.simplified.borrow();
+
+        unit.iter()
+            .try_fold((Unit::default(), 0), |(acc, depth), (ident, power)| {
+                let (unit, ident_depth) = simplified.get(&ident).cloned().unwrap_or_else(|| {
+                    (Unit::from_exponents([(ident, 1)]), self.depth())
+                });
+                let unit = acc.checked_mul(unit.checked_power(power)?)?;
+                Some((unit, depth.max(ident_depth)))
+            })
+    }
 
-            return Err(DigifyError::new(kind, span));
-        }
+    /// Innermost visible variable `ident` with the depth of its scope
+    fn find(&self, ident: Symbol) -> Option<(usize, &Variable)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, scope)| Some((depth, scope.get_existing(ident)?.item())))
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(Scope::default())
+    }
 
-        let variable = Variable::Unit(unit);
-        let variable = Item::new(variable, ident.span);
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop();
 
-        // ident.item is used instead of ident.as_str() because the borrow checker
-        // can not know that the ident.as_str() only borrow ident.item
-        self.variables.insert(ident.item, variable);
-        Ok(())
+        let depth = self.scopes.len();
+        self.simplified
+            .get_mut()
+            .retain(|_, (_, dependency)| *dependency < depth);
     }
+}
 
-    fn contains(&self, ident: &str) -> bool {
-        self.variables.contains_key(ident)
+impl Default for TowerScope {
+    fn default() -> Self {
+        Self {
+            scopes: vec![Scope::default()],
+            simplified: RefCell::default(),
+        }
     }
+}
 
-    fn get(&self, ident: &Istr<'a>) -> Result<'a, &IVariable<'a>> {
-        if let Some(variable) = self.variables.get(ident.as_str()) {
-            Ok(variable)
-        } else {
-            let kind = ErrorKind::VariableNotDeclared(ident.as_str().to_owned());
-            let span = ident.span.clone();
+impl Scope {
+    fn define(&mut self, declaration: Declaration, symbole: Symbol) -> Result<()> {
+        let axiom = Axiom::new(declaration.symbol, symbole);
+        self.declare(declaration, Variable::Axiom(axiom))
+    }
+
+    fn insert(&mut self, declaration: Declaration, unit: Unit) -> Result<()> {
+        self.declare(declaration, Variable::Unit(unit))
+    }
 
-            Err(DigifyError::new(kind, span))
+    fn declare(&mut self, declaration: Declaration, variable: Variable) -> Result<()> {
+        let Declaration { name, symbol } = declaration;
+        let previous = self.names.get(&name.item).copied().unwrap_or(symbol);
+        if let Some(previous) = self.variables.get(&previous) {
+            let kind = ErrorKind::VariableAlreadyDeclared(name.as_str().to_owned());
+            let span = name.span;
+            let error = DigifyError::new(kind, span);
+
+            return Err(error.with_label(previous.span, "previously declared here"));
         }
+
+        self.names.insert(name.item, symbol);
+        self.variables.insert(symbol, Item::new(variable, name.span));
+        Ok(())
     }
 
-    fn get_existing(&self, ident: &str) -> Option<&IVariable<'a>> {
-        self.variables.get(ident)
+    fn get_existing(&self, ident: Symbol) -> Option<&IVariable> {
+        self.variables.get(&ident)
     }
 }
@@ -0,0 +1,118 @@
+use std::collections::BinaryHeap;
+
+use crate::error::Result;
+use crate::parser::{IExpr, Istr};
+
+use super::algebra;
+use super::product::{self, Product};
+use super::resolve::{IBinding, IRExpr};
+use super::{first_error, variable, Interpreter};
+
+/// Exponent bound used when a search does not give one
+pub const DEFAULT_SEARCH_BOUND: u32 = 3;
+/// Maximum number of products reported by a search
+pub const MAX_SEARCH_RESULTS: usize = 10;
+/// Choices of the free exponents tried between two checks of the time budget
+const TIME_CHECK_INTERVAL: u32 = 4096;
+
+impl<'a> Interpreter<'a> {
+    /// Products of `variables` with exponents in `-bound..=bound` whose unit is `target`,
+    /// the simplest first
+    pub fn search(
+        &self,
+        target: IExpr,
+        variables: &[Istr],
+        bound: u32,
+    ) -> Result<Vec<Product>> {
+        let mut resolver = self.resolver();
+        let target = resolver.expr(target);
+        let variables = resolver.idents(variables.to_vec());
+        let (target, variables) = resolver.finish((target, variables)).map_err(first_error)?;
+
+        self.search_of(target, &variables, bound)
+    }
+
+    pub(super) fn search_of(
+        &self,
+        target: IRExpr,
+        variables: &[IBinding],
+        bound: u32,
+    ) -> Result<Vec<Product>> {
+        let span = target.span;
+        let target = self.eval_expr(target)?.simplify(&self.scopes, span)?;
+
+        let mut units = self.simplified(variables)?;
+        units.push(target.clone());
+        let axioms = product::axioms(&units);
+        units.pop();
+
+        let matrix = product::dimension_matrix(&axioms, &units);
+        let expected = axioms
+            .iter()
+            .map(|axiom| target.exponent(*axiom))
+            .collect::<Vec<_>>();
+
+        let bound = i32::try_from(bound).map_err(|_| variable::overflow(span))?;
+        let solutions = algebra::solutions(&matrix, &expected, units.len())
+            .map_err(|_| variable::overflow(span))?;
+        let Some(solutions) = solutions else {
+            return Ok(Vec::new());
+        };
+        let choices = 2 * u64::from(bound.unsigned_abs()) + 1;
+        let candidates = u32::try_from(solutions.free.len())
+            .ok()
+            .and_then(|free| choices.checked_pow(free));
+        self.limits.check_search(candidates, span)?;
+
+        // Only the free exponents are enumerated, the others follow from them. The simplest
+        // products are kept in a max-heap whose top is the first to leave.
+        let mut free = vec![-bound; solutions.free.len()];
+        let mut powers = vec![0; variables.len()];
+        let mut found = BinaryHeap::new();
+        let mut tried = 0u32;
+
+        loop {
+            tried = tried.wrapping_add(1);
+            if tried.is_multiple_of(TIME_CHECK_INTERVAL) {
+                self.check_time(span)?;
+            }
+
+            for (column, power) in solutions.free.iter().zip(&free) {
+                powers[*column] = *power;
+            }
+            // The empty product trivially matches a dimensionless target
+            if solutions.complete(&mut powers, bound) && powers.iter().any(|power| *power != 0) {
+                found.push(simplest(&powers));
+                if found.len() > MAX_SEARCH_RESULTS {
+                    found.pop();
+                }
+            }
+
+            // Next choice, like an odometer
+            let Some(i) = free.iter().position(|power| *power < bound) else {
+                break;
+            };
+            free[..i].iter_mut().for_each(|power| *power = -bound);
+            free[i] += 1;
+        }
+
+        let products = found
+            .into_sorted_vec()
+            .iter()
+            .map(|(_, powers)| Product::new(variables, powers))
+            .collect();
+
+        Ok(products)
+    }
+}
+
+/// Key of `powers` in a search, the simplest first: smallest total exponent, then fewest
+/// factors, then fewest divisions, then the order of the exponents from the last variable
+fn simplest(powers: &[i32]) -> ((u64, usize, usize, Vec<i32>), Vec<i32>) {
+    let total = powers.iter().map(|power| power.unsigned_abs() as u64).sum::<u64>();
+    let factors = powers.iter().filter(|power| **power != 0).count();
+    let divisions = powers.iter().filter(|power| **power < 0).count();
+    let order = powers.iter().rev().copied().collect();
+
+    ((total, factors, divisions, order), powers.to_vec())
+}
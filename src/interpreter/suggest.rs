@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use crate::symbol::Symbol;
+
+/// Most names suggested for an undeclared one
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Visible names close to the undeclared `name`, the closest first. A name is close when it
+/// differs only by its case, when it is written with few edits, or when `name` is the
+/// symbol of its axiom, like `m` for `define D "m"`.
+///
+/// `candidates` are the visible names with the symbol of their axiom, if any.
+pub fn suggestions(
+    name: &str,
+    candidates: impl IntoIterator<Item = (Symbol, Option<Symbol>)>,
+) -> Vec<String> {
+    let lowercase = name.to_lowercase();
+    let length = name.chars().count();
+    let max_distance = length.max(3) / 3;
+
+    let mut close = candidates
+        .into_iter()
+        .filter_map(|(candidate, symbole)| {
+            let candidate = candidate.as_str();
+            let distance = if symbole.is_some_and(|symbole| symbole.as_str() == name) {
+                0
+            } else {
+                // As many edits as characters in one of the names keep nothing of it
+                let distance = distance(&lowercase, &candidate.to_lowercase());
+                let shortest = length.min(candidate.chars().count());
+                (distance < shortest).then_some(distance)?
+            };
+
+            (candidate != name && distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect::<Vec<_>>();
+    close.sort_unstable();
+    // A shadowed name is a candidate once, for its closest variable
+    let mut seen = HashSet::new();
+    close.retain(|(_, candidate)| seen.insert(*candidate));
+
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_owned())
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
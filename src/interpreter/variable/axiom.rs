@@ -1,16 +1,18 @@
-use std::{cell::OnceCell, collections::HashMap};
+use std::cell::OnceCell;
+
+use crate::symbol::Symbol;
 
 use super::Unit;
 
 #[derive(Debug, Clone)]
-pub struct Axiom<'a> {
-    ident: &'a str,
-    symbole: &'a str,
-    unit: OnceCell<Unit<'a>>,
+pub struct Axiom {
+    ident: Symbol,
+    symbole: Symbol,
+    unit: OnceCell<Unit>,
 }
 
-impl<'a> Axiom<'a> {
-    pub fn new(ident: &'a str, symbole: &'a str) -> Self {
+impl Axiom {
+    pub fn new(ident: Symbol, symbole: Symbol) -> Self {
         Self {
             ident,
             symbole,
@@ -18,21 +20,17 @@ impl<'a> Axiom<'a> {
         }
     }
 
-    pub fn symbole(&self) -> &str {
+    pub fn symbole(&self) -> Symbol {
         self.symbole
     }
 
-    pub fn as_unit(&self) -> &Unit<'a> {
-        self.unit.get_or_init(|| {
-            let mut top = HashMap::new();
-            top.insert(self.ident, 1);
-
-            Unit::new(top, HashMap::new())
-        })
+    pub fn as_unit(&self) -> &Unit {
+        self.unit
+            .get_or_init(|| Unit::from_exponents([(self.ident, 1)]))
     }
 }
 
-impl PartialEq for Axiom<'_> {
+impl PartialEq for Axiom {
     fn eq(&self, other: &Self) -> bool {
         self.symbole == other.symbole && self.ident == other.ident
     }
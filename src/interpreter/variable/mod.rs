@@ -4,32 +4,27 @@ mod unit;
 pub use axiom::Axiom;
 pub use unit::Unit;
 
-use crate::{interpreter::scope::TowerScope, parser::Item};
+pub(crate) use unit::overflow;
 
-pub type IVariable<'a> = Item<'a, Variable<'a>>;
+use crate::parser::Item;
+
+pub type IVariable = Item<Variable>;
 
 #[derive(Debug, Clone)]
-pub enum Variable<'a> {
-    Unit(Unit<'a>),
-    Axiom(Axiom<'a>),
+pub enum Variable {
+    Unit(Unit),
+    Axiom(Axiom),
 }
 
-impl<'a> Variable<'a> {
-    // fn as_unit(&self) -> &Unit<'a> {
+impl Variable {
+    // fn as_unit(&self) -> &Unit {
     //     match self {
     //         Self::Unit(unit) => unit,
     //         Self::Axiom(axiom) => axiom.as_unit(),
     //     }
     // }
 
-    fn simplify(&self, scopes: &TowerScope<'a>) -> &Unit<'a> {
-        match self {
-            Self::Unit(unit) => unit.simplify(scopes),
-            Self::Axiom(axiom) => axiom.as_unit(),
-        }
-    }
-
-    // pub fn eq(&'a self, other: &'a Self, scopes: &'a TowerScope<'a>) -> bool {
+    // pub fn eq(&'a self, other: &'a Self, scopes: &'a TowerScope) -> bool {
     //     match (self, other) {
     //         (Self::Axiom(axiom1), Self::Axiom(axiom2)) => axiom1 == axiom2,
     //         (Variable::Unit(unit), Variable::Axiom(axiom))
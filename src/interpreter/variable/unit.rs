@@ -1,304 +1,445 @@
-use std::cell::OnceCell;
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::ops;
 
-// use color_eyre::eyre::Result;
-
-use crate::interpreter::{scope::TowerScope, variable::Variable};
-use crate::parser::Expr;
-use crate::error::Result;
-
-// Invariant: top and bottom &str are always existing in the scopes
-#[derive(Debug, Clone, Default)]
-pub struct Unit<'a> {
-    top: HashMap<&'a str, u32>,
-    bottom: HashMap<&'a str, u32>,
-    simplify: Box<OnceCell<Unit<'a>>>,
+use smallvec::SmallVec;
+
+use crate::error::{AssertFailure, DigifyError, ErrorKind, Result};
+use crate::interpreter::algebra::{self, Ratio};
+use crate::interpreter::{product, scope::TowerScope, variable::Variable};
+use crate::interpreter::resolve::{IBinding, IRExpr, RExpr};
+use crate::parser::Span;
+use crate::render::{Factor, Renderer};
+use crate::symbol::Symbol;
+
+/// Product of idents with their exponent, sorted by symbol and without zero exponent,
+/// so that equal units have equal representations. The idents are the symbols of resolved
+/// variables, a symbol unknown to the scopes is left as is rather than simplified.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Unit {
+    exponents: Exponents,
 }
 
-impl<'a> Unit<'a> {
-    pub fn new(top: HashMap<&'a str, u32>, bottom: HashMap<&'a str, u32>) -> Self {
-        Self {
-            top,
-            bottom,
-            simplify: Box::new(OnceCell::new()),
-        }
+/// Enough inline room for the seven SI base units
+type Exponents = SmallVec<[(Symbol, i32); 8]>;
+
+impl Unit {
+    /// Build a unit from signed exponents, the exponents of a repeated ident are added
+    ///
+    /// # Panics
+    ///
+    /// If the exponents of a repeated ident overflow once added, see
+    /// [`Unit::checked_from_exponents`].
+    pub fn from_exponents(exponents: impl IntoIterator<Item = (Symbol, i32)>) -> Self {
+        Self::checked_from_exponents(exponents).expect("exponent overflow")
     }
 
-    pub fn top(&self) -> &HashMap<&'a str, u32> {
-        &self.top
+    /// Build a unit from signed exponents, `None` if the exponents of a repeated ident
+    /// overflow once added
+    pub fn checked_from_exponents(
+        exponents: impl IntoIterator<Item = (Symbol, i32)>,
+    ) -> Option<Self> {
+        let mut exponents = exponents.into_iter().collect::<Exponents>();
+        exponents.sort_unstable_by_key(|(ident, _)| *ident);
+
+        let mut merged = Exponents::new();
+        for (ident, exponent) in exponents {
+            match merged.last_mut() {
+                Some((last, total)) if *last == ident => {
+                    *total = total.checked_add(exponent)?
+                }
+                _ => merged.push((ident, exponent)),
+            }
+        }
+        merged.retain(|(_, exponent)| *exponent != 0);
+
+        Some(Self { exponents: merged })
     }
 
-    pub fn bottom(&self) -> &HashMap<&'a str, u32> {
-        &self.bottom
+    /// Idents of the unit with their signed exponent, in the order of their symbols
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, i32)> + '_ {
+        self.exponents.iter().copied()
     }
 
-    pub fn simplify(&self, scopes: &TowerScope<'a>) -> &Unit<'a> {
-        self.simplify.get_or_init(|| {
-            let top = self
-                .top
-                .iter()
-                .map(|(ident, power)| {
-                    scopes
-                        .get_existing(ident)
-                        .unwrap()
-                        .item
-                        .simplify(scopes)
-                        .clone()
-                        .power(*power)
-                })
-                .reduce(|acc, variable| acc * variable)
-                .unwrap_or_default();
-
-            let bottom = self
-                .bottom
-                .iter()
-                .map(|(ident, power)| {
-                    scopes
-                        .get_existing(ident)
-                        .unwrap()
-                        .item
-                        .simplify(scopes)
-                        .clone()
-                        .power(*power)
-                })
-                .reduce(|acc, variable| acc * variable)
-                .unwrap_or_default();
-
-            top / bottom
-        })
+    /// Signed exponent of `ident`, 0 if the unit does not contain it
+    pub fn exponent(&self, ident: Symbol) -> i32 {
+        self.exponents
+            .binary_search_by_key(&ident, |(ident, _)| *ident)
+            .map_or(0, |i| self.exponents[i].1)
     }
 
-    fn power(mut self, power: u32) -> Self {
-        self.top.values_mut().for_each(|value| *value *= power);
-        self.bottom.values_mut().for_each(|value| *value *= power);
-        self
+    /// Signed exponents of every ident of the unit, sorted by ident
+    pub fn exponents(&self) -> Vec<(Symbol, i32)> {
+        let mut exponents = self.iter().collect::<Vec<_>>();
+        exponents.sort_unstable_by_key(|(ident, _)| ident.as_str());
+
+        exponents
     }
 
-    pub fn eq(&self, other: &Unit<'a>, scopes: &TowerScope<'a>) -> bool {
-        let unit1 = self.simplify(scopes);
-        let unit2 = other.simplify(scopes);
+    /// Product of both units, `None` if an exponent overflows
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.merge(rhs, 1)
+    }
 
-        unit1.top() == unit2.top() && unit1.bottom() == unit2.bottom()
+    /// Quotient of both units, `None` if an exponent overflows
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.merge(rhs, -1)
     }
 
-    pub fn to_string(&self, scopes: &TowerScope<'a>) -> String {
-        let mut pos_ident = self.top.keys().copied().collect::<Vec<_>>();
-        let mut neg_ident = self.bottom.keys().copied().collect::<Vec<_>>();
-        pos_ident.sort_unstable();
-        neg_ident.sort_unstable();
+    /// Unit raised to `power`, `None` if an exponent overflows
+    pub fn checked_power(mut self, power: i32) -> Option<Self> {
+        if power == 0 {
+            return Some(Self::default());
+        }
 
-        if pos_ident.is_empty() && neg_ident.is_empty() {
-            return "1".to_string();
+        for (_, exponent) in self.exponents.iter_mut() {
+            *exponent = exponent.checked_mul(power)?;
         }
+        Some(self)
+    }
 
-        if pos_ident.len() == 1 && neg_ident.is_empty() {
-            let variable = scopes.get_existing(pos_ident[0]).unwrap().item();
+    /// Unit reduced to axioms, the error is located at `span`
+    pub(crate) fn simplify(&self, scopes: &TowerScope, span: Span) -> Result<Unit> {
+        scopes.simplify(self).ok_or_else(|| overflow(span))
+    }
 
-            match variable {
-                Variable::Axiom(axiom) => return axiom.symbole().to_string(),
-                Variable::Unit(unit) => return unit.to_string(scopes),
-            }
-        }
+    /// Merge the exponents of both units, `sign` applying to the ones of `rhs`
+    fn merge(self, rhs: Self, sign: i32) -> Option<Self> {
+        let mut exponents = Exponents::new();
+        let mut lhs = self.exponents.into_iter().peekable();
+        let mut rhs = rhs.exponents.into_iter().peekable();
 
-        let mut string = String::new();
-
-        for ident in pos_ident {
-            let power = self.top[ident];
-            if power == 1 {
-                string.push_str(ident);
-                string.push(' ');
-            } else {
-                string.push_str(ident);
-                string.push_str(&power.to_string());
-                string.push(' ');
-            }
-        }
+        loop {
+            let (ident, exponent) = match (lhs.peek(), rhs.peek()) {
+                (Some((ident1, _)), Some((ident2, _))) => match ident1.cmp(ident2) {
+                    Ordering::Less => lhs.next()?,
+                    Ordering::Greater => {
+                        let (ident, exponent) = rhs.next()?;
+                        (ident, sign.checked_mul(exponent)?)
+                    }
+                    Ordering::Equal => {
+                        let (ident, exponent1) = lhs.next()?;
+                        let (_, exponent2) = rhs.next()?;
+                        (ident, exponent1.checked_add(sign.checked_mul(exponent2)?)?)
+                    }
+                },
+                (Some(_), None) => lhs.next()?,
+                (None, Some(_)) => {
+                    let (ident, exponent) = rhs.next()?;
+                    (ident, sign.checked_mul(exponent)?)
+                }
+                (None, None) => break,
+            };
 
-        for ident in neg_ident {
-            let power = self.bottom[ident];
-            if power == 1 {
-                string.push_str(ident);
-                string.push_str("-1 ");
-            } else {
-                string.push_str(ident);
-                string.push('-');
-                string.push_str(&power.to_string());
-                string.push(' ');
+            if exponent != 0 {
+                exponents.push((ident, exponent));
             }
         }
 
-        string
+        Some(Self { exponents })
+    }
+
+    pub(crate) fn eq(&self, other: &Unit, scopes: &TowerScope, span: Span) -> Result<bool> {
+        Ok(self.simplify(scopes, span)? == other.simplify(scopes, span)?)
+    }
+
+    /// Why `self`, the left side of an assertion, differs from `other`
+    pub(crate) fn diff(
+        &self,
+        other: &Unit,
+        scopes: &TowerScope,
+        span: Span,
+    ) -> Result<AssertFailure> {
+        let left = self.simplify(scopes, span)?;
+        let right = other.simplify(scopes, span)?;
+        let axioms = |unit: &Unit| {
+            let factors = unit.named_factors(scopes, false);
+            Self::render_factors(&factors, scopes, Renderer::default())
+        };
+
+        let mut idents = left
+            .iter()
+            .chain(right.iter())
+            .map(|(ident, _)| ident)
+            .collect::<Vec<_>>();
+        idents.sort_unstable_by_key(|ident| ident.as_str());
+        idents.dedup();
+        let exponents = idents
+            .into_iter()
+            .map(|ident| {
+                let name = match scopes.get_existing(ident).map(|variable| variable.item()) {
+                    Some(Variable::Axiom(axiom)) => axiom.symbole(),
+                    _ => ident,
+                };
+                (name.as_str().to_owned(), left.exponent(ident), right.exponent(ident))
+            })
+            .collect();
+        let residual = left.clone().checked_div(right.clone());
+
+        Ok(AssertFailure {
+            expected: self.to_string(scopes),
+            actual: other.to_string(scopes),
+            expected_axioms: axioms(&left),
+            actual_axioms: axioms(&right),
+            exponents,
+            residual: residual.map(|residual| residual.to_string(scopes)),
+        })
+    }
+
+    pub(crate) fn to_string(&self, scopes: &TowerScope) -> String {
+        self.render(scopes, Renderer::default())
+    }
+
+    pub(crate) fn render(&self, scopes: &TowerScope, renderer: Renderer) -> String {
+        let factors = match renderer {
+            Renderer::Canonical => self.exponents(),
+            _ => self.factors(scopes),
+        };
+        Self::render_factors(&factors, scopes, renderer)
     }
 
-    pub fn println(&self, scopes: &TowerScope<'a>) {
-        println!("{}", self.to_string(scopes));
+    /// Rendering of a unit written in the basis of a `%[...]`: neither the preferred units
+    /// nor the definition of a lone basis unit replace its factors
+    pub(crate) fn render_in_basis(&self, scopes: &TowerScope, renderer: Renderer) -> String {
+        let factors = match renderer {
+            Renderer::Canonical => self.exponents(),
+            _ => self.named_factors(scopes, false),
+        };
+        Self::render_factors(&factors, scopes, renderer)
     }
 
-    pub fn from(value: Expr<'a>, scopes: &TowerScope<'a>) -> Result<'a, Self> {
-        fn insert_in_frac<'b>(
-            top: &mut HashMap<&'b str, u32>,
-            bottom: &mut HashMap<&'b str, u32>,
-            expr: Expr<'b>,
-            power: u32,
-            simplify: bool,
-            scopes: &TowerScope<'b>,
-        ) -> Result<'b, ()> {
-            match expr {
-                Expr::Ident(ident) => {
-                    if simplify {
-                        let variable = scopes.get(ident)?.item.simplify(scopes);
-
-                        for (ident, power) in variable.top() {
-                            *top.entry(ident).or_default() += power;
-                        }
-                        for (ident, power) in variable.bottom() {
-                            *bottom.entry(ident).or_default() += power;
-                        }
-                    } else {
-                        *top.entry(ident.as_str()).or_default() += power
+    /// Render idents with their exponent. Axioms are named by their symbol, except in the
+    /// canonical style, and other units by their ident.
+    fn render_factors(
+        factors: &[(Symbol, i32)],
+        scopes: &TowerScope,
+        renderer: Renderer,
+    ) -> String {
+        let factors = factors
+            .iter()
+            .map(|(ident, power)| {
+                let (name, symbol) = match scopes.get_existing(*ident).map(|v| v.item()) {
+                    Some(Variable::Axiom(axiom)) if renderer != Renderer::Canonical => {
+                        (axiom.symbole(), true)
                     }
+                    _ => (*ident, false),
+                };
+                Factor {
+                    name: name.as_str(),
+                    power: *power,
+                    symbol,
                 }
-                Expr::Mul(expr1, expr2) => {
-                    insert_in_frac(top, bottom, expr1.item, power, simplify, scopes)?;
-                    insert_in_frac(top, bottom, expr2.item, power, simplify, scopes)?;
-                }
-                Expr::Div(expr1, expr2) => {
-                    insert_in_frac(top, bottom, expr1.item, power, simplify, scopes)?;
-                    insert_in_frac(bottom, top, expr2.item, power, simplify, scopes)?;
-                }
-                Expr::Power(expr, number) => match number.cmp(&0) {
-                    Ordering::Less => insert_in_frac(
-                        bottom,
-                        top,
-                        expr.item,
-                        power * -number as u32,
-                        simplify,
-                        scopes,
-                    )?,
-                    Ordering::Greater => insert_in_frac(
-                        top,
-                        bottom,
-                        expr.item,
-                        power * number as u32,
-                        simplify,
-                        scopes,
-                    )?,
-                    Ordering::Equal => (),
-                },
-                Expr::Simplify(expr) => {
-                    insert_in_frac(top, bottom, expr.item, power, true, scopes)?
-                }
-                Expr::None => (),
-            }
+            })
+            .collect::<Vec<_>>();
 
-            Ok(())
+        renderer.render_factors(&factors)
+    }
+
+    /// Printed factors of the unit with their exponent, positive exponents first
+    pub(crate) fn factors(&self, scopes: &TowerScope) -> Vec<(Symbol, i32)> {
+        let preferred = scopes.preferred();
+        // A unit whose simplification overflows is printed as written
+        match scopes.simplify(self) {
+            Some(simplified) if !preferred.is_empty() => simplified
+                .factor(&preferred, scopes)
+                .named_factors(scopes, false),
+            _ => self.named_factors(scopes, true),
         }
+    }
 
-        let mut top = HashMap::new();
-        let mut bottom = HashMap::new();
+    /// Rewrite a unit made of axioms with the `preferred` units, one factor at a time
+    /// as long as it lowers the total of the absolute exponents
+    fn factor(&self, preferred: &[Symbol], scopes: &TowerScope) -> Unit {
+        // Exponents are widened, a factor only lowers the total so the result fits back
+        fn cost(factors: &HashMap<Symbol, i64>, remainder: &HashMap<Symbol, i64>) -> i64 {
+            factors.values().chain(remainder.values()).map(|e| e.abs()).sum()
+        }
 
-        insert_in_frac(&mut top, &mut bottom, value, 1, false, scopes)?;
-        let bottom_idents = bottom.keys().copied().collect::<Vec<_>>();
+        fn apply(
+            factors: &mut HashMap<Symbol, i64>,
+            remainder: &mut HashMap<Symbol, i64>,
+            ident: Symbol,
+            unit: &Unit,
+            power: i64,
+        ) {
+            *factors.entry(ident).or_default() += power;
+            for (axiom, exponent) in unit.exponents() {
+                *remainder.entry(axiom).or_default() -= i64::from(exponent) * power;
+            }
+            factors.retain(|_, exponent| *exponent != 0);
+            remainder.retain(|_, exponent| *exponent != 0);
+        }
 
-        for ident in bottom_idents {
-            let mut is_cancel = false;
-            if let Some(pos_power) = top.get_mut(ident) {
-                match bottom[ident].cmp(pos_power) {
-                    Ordering::Equal => {
-                        is_cancel = true;
-                    }
-                    Ordering::Greater => {
-                        is_cancel = true;
-                        *bottom.get_mut(ident).unwrap() -= *pos_power;
-                    }
-                    Ordering::Less => {
-                        *pos_power -= bottom[ident];
-                        bottom.remove(ident);
+        let preferred = preferred
+            .iter()
+            .filter_map(|ident| Some((*ident, scopes.simplified_existing(*ident)?)))
+            .collect::<Vec<_>>();
+
+        let mut factors = HashMap::new();
+        let mut remainder = self
+            .iter()
+            .map(|(axiom, exponent)| (axiom, i64::from(exponent)))
+            .collect::<HashMap<_, _>>();
+
+        loop {
+            let mut best = None;
+            let mut best_cost = cost(&factors, &remainder);
+
+            for (ident, unit) in &preferred {
+                for power in [1, -1, 2, -2, 3, -3] {
+                    let mut new_factors = factors.clone();
+                    let mut new_remainder = remainder.clone();
+                    apply(&mut new_factors, &mut new_remainder, *ident, unit, power);
+
+                    let new_cost = cost(&new_factors, &new_remainder);
+                    if new_cost < best_cost {
+                        best = Some((*ident, unit, power));
+                        best_cost = new_cost;
                     }
                 }
             }
 
-            if is_cancel {
-                top.remove(ident);
-                bottom.remove(ident);
-            }
+            let Some((ident, unit, power)) = best else {
+                break;
+            };
+            apply(&mut factors, &mut remainder, ident, unit, power);
         }
 
-        Ok(Self {
-            top,
-            bottom,
-            simplify: Box::new(OnceCell::new()),
-        })
+        let mut exponents = factors;
+        for (axiom, exponent) in remainder {
+            *exponents.entry(axiom).or_default() += exponent;
+        }
+
+        let exponents = exponents
+            .into_iter()
+            .map(|(ident, exponent)| Some((ident, i32::try_from(exponent).ok()?)))
+            .collect::<Option<Vec<_>>>();
+        exponents
+            .and_then(Unit::checked_from_exponents)
+            .unwrap_or_else(|| self.clone())
     }
-}
 
-impl<'a> ops::Mul for Unit<'a> {
-    type Output = Self;
+    /// Idents of the unit with their exponent, positive exponents first. A lone unit variable
+    /// is replaced by its own factors when `expand` is set.
+    fn named_factors(&self, scopes: &TowerScope, expand: bool) -> Vec<(Symbol, i32)> {
+        let mut unit = self;
+        // Chains of aliases are followed with a loop, they end as an alias is declared after
+        // the unit it names
+
+        while let [(ident, 1)] = unit.exponents[..] {
+            match scopes.get_existing(ident).map(|variable| variable.item()) {
+                Some(Variable::Unit(alias)) if expand => unit = alias,
+                _ => break,
+            }
+        }
 
-    fn mul(mut self, rhs: Self) -> Self::Output {
-        self.simplify = Box::new(OnceCell::new());
+        let (pos_ident, neg_ident): (Vec<_>, Vec<_>) = unit
+            .exponents()
+            .into_iter()
+            .partition(|(_, power)| *power > 0);
 
-        // Insert rhs.top values inside self.top
-        for (ident, pos_power) in rhs.top {
-            let mut is_cancel = false;
-            if let Some(neg_power) = self.bottom.get_mut(ident) {
-                match pos_power.cmp(neg_power) {
-                    Ordering::Equal => {
-                        is_cancel = true;
-                    }
-                    Ordering::Greater => {
-                        is_cancel = true;
-                        *self.top.entry(ident).or_default() += pos_power - *neg_power;
-                    }
-                    Ordering::Less => *neg_power -= pos_power,
-                }
-            } else {
-                *self.top.entry(ident).or_default() += pos_power;
-            }
-            if is_cancel {
-                self.bottom.remove(ident);
-            }
+        pos_ident.into_iter().chain(neg_ident).collect()
+    }
+
+    /// Exponents of the `basis` units whose product is `self`, which must only contain axioms
+    fn express_in(
+        &self,
+        basis: &[IBinding],
+        span: Span,
+        scopes: &TowerScope,
+    ) -> Result<Vec<(Symbol, i32)>> {
+        let units = basis
+            .iter()
+            .map(|variable| scopes.simplified(variable))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut axioms = units.clone();
+        axioms.push(self.clone());
+        let axioms = product::axioms(&axioms);
+
+        let matrix = product::dimension_matrix(&axioms, &units);
+        let expected = axioms
+            .iter()
+            .map(|axiom| self.exponent(*axiom))
+            .collect::<Vec<_>>();
+
+        let cannot_express = |reason: &str| {
+            let basis = basis
+                .iter()
+                .map(|variable| variable.item.symbol.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let kind = ErrorKind::CannotExpress(basis, reason.to_owned());
+            DigifyError::new(kind, span)
+        };
+
+        // With a dependent basis the exponents are not unique, and which ones are integers
+        // would depend on the order of the basis
+        let relations = product::dimension_matrix(&product::axioms(&units), &units);
+        let relations = algebra::null_space(&relations, units.len()).map_err(|_| overflow(span))?;
+        if let Some(relation) = relations.first() {
+            let relation = product::Product::new(basis, relation);
+            let reason = format!("its units are not independent, {} is dimensionless", relation);
+            return Err(cannot_express(&reason));
         }
 
-        // Insert rhs.bottom values inside self.bottom
-        for (ident, neg_power) in rhs.bottom {
-            let mut is_cancel = false;
-            if let Some(pos_power) = self.top.get_mut(ident) {
-                match neg_power.cmp(pos_power) {
-                    Ordering::Equal => {
-                        is_cancel = true;
-                    }
-                    Ordering::Greater => {
-                        is_cancel = true;
-                        *self.bottom.entry(ident).or_default() += neg_power - *pos_power;
-                    }
-                    Ordering::Less => *pos_power -= neg_power,
-                }
-            } else {
-                *self.bottom.entry(ident).or_default() += neg_power;
-            }
-            if is_cancel {
-                self.top.remove(ident);
-            }
+        let solution = algebra::solve(&matrix, &expected, units.len())
+            .map_err(|_| overflow(span))?
+            .ok_or_else(|| cannot_express("they do not span the unit"))?;
+        if !solution.iter().all(Ratio::is_integer) {
+            return Err(cannot_express("it needs a fractional exponent"));
         }
 
-        self
+        basis
+            .iter()
+            .zip(solution)
+            .map(|(variable, exponent)| {
+                let exponent = i32::try_from(exponent.num()).map_err(|_| overflow(span))?;
+                Ok((variable.item.symbol, exponent))
+            })
+            .collect()
     }
-}
 
-impl<'a> ops::Div for Unit<'a> {
-    type Output = Self;
+    pub(crate) fn from(value: IRExpr, scopes: &TowerScope) -> Result<Self> {
+        Self::from_expr(value, false, scopes)
+    }
 
-    #[allow(clippy::suspicious_arithmetic_impl)]
-    fn div(self, rhs: Self) -> Self::Output {
-        let inverse_rhs = Self {
-            top: rhs.bottom,
-            bottom: rhs.top,
-            simplify: rhs.simplify,
+    /// Unit of `expr`, with its idents replaced by axioms when `simplify` is set
+    fn from_expr(expr: IRExpr, simplify: bool, scopes: &TowerScope) -> Result<Self> {
+        let span = expr.span;
+        let unit = match expr.item {
+            RExpr::Variable(variable) if simplify => scopes.simplified(&variable)?,
+            RExpr::Variable(variable) => {
+                scopes.get(&variable)?;
+                Self::from_exponents([(variable.item.symbol, 1)])
+            }
+            RExpr::Mul(expr1, expr2) => {
+                let unit1 = Self::from_expr(*expr1, simplify, scopes)?;
+                let unit2 = Self::from_expr(*expr2, simplify, scopes)?;
+                unit1.checked_mul(unit2).ok_or_else(|| overflow(span))?
+            }
+            RExpr::Div(expr1, expr2) => {
+                let unit1 = Self::from_expr(*expr1, simplify, scopes)?;
+                let unit2 = Self::from_expr(*expr2, simplify, scopes)?;
+                unit1.checked_div(unit2).ok_or_else(|| overflow(span))?
+            }
+            RExpr::Power(expr, number) => Self::from_expr(*expr, simplify, scopes)?
+                .checked_power(number)
+                .ok_or_else(|| overflow(span))?,
+            // Inside an other simplification the basis would be reduced to axioms anyway
+            RExpr::Simplify(expr, Some(basis)) if !simplify => {
+                let unit = Self::from_expr(*expr, true, scopes)?;
+                Self::checked_from_exponents(unit.express_in(&basis, span, scopes)?)
+                    .ok_or_else(|| overflow(span))?
+            }
+            RExpr::Simplify(expr, _) => Self::from_expr(*expr, true, scopes)?,
+            RExpr::None => Self::default(),
         };
 
-        self * inverse_rhs
+        Ok(unit)
     }
 }
+
+/// Error of an exponent that does not fit an `i32`, located on the offending operation
+pub(crate) fn overflow(span: Span) -> DigifyError {
+    DigifyError::new(ErrorKind::ExponentOverflow, span)
+}
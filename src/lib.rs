@@ -0,0 +1,42 @@
+//! Digify declares axioms and units built from them, then checks, simplifies
+//! and prints their dimensions.
+//!
+//! ```
+//! use digify::Interpreter;
+//!
+//! let mut interpreter = Interpreter::default();
+//! interpreter.eval_str("define D \"m\"; define T \"s\"; let Vit = D / T;").unwrap();
+//!
+//! let unit = interpreter.parse_unit("Vit T").unwrap();
+//! assert_eq!(interpreter.simplify(&unit).unwrap().exponents(), vec![("D".into(), 1)]);
+//! ```
+//!
+//! The `print` statements write to the standard output, unless an other output is given:
+//!
+//! ```
+//! use digify::Interpreter;
+//!
+//! let mut output = Vec::new();
+//! let mut interpreter = Interpreter::default();
+//! interpreter.set_output(&mut output);
+//! interpreter.eval_str("define T \"s\"; print T;").unwrap();
+//! drop(interpreter);
+//!
+//! assert_eq!(String::from_utf8(output).unwrap(), "s\n");
+//! ```
+
+mod error;
+mod interpreter;
+mod limits;
+mod parser;
+mod render;
+mod source;
+mod symbol;
+
+pub use error::{AssertFailure, DigifyError, ErrorKind, Label, Result};
+pub use interpreter::{Axiom, Interpreter, Product, Program, Unit, Variable};
+pub use limits::{Limits, DEFAULT_MAX_SEARCH_CANDIDATES};
+pub use parser::{DigifyParser, Element, Expr, IExpr, IStmt, Istr, Item, Position, Span, Stmt};
+pub use render::{ParseRendererError, Renderer};
+pub use source::{FileId, SourceMap};
+pub use symbol::Symbol;
@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+
+use crate::error::{DigifyError, ErrorKind, Result};
+use crate::interpreter::Unit;
+use crate::parser::Span;
+
+/// Bounds on the work of an [`Interpreter`](crate::Interpreter), to evaluate untrusted files.
+/// Each limit is disabled when `None`, as in the default, except the candidates of a search
+/// which are [`DEFAULT_MAX_SEARCH_CANDIDATES`] by default.
+///
+/// ```
+/// use std::time::Duration;
+/// use digify::{Interpreter, Limits};
+///
+/// let mut interpreter = Interpreter::default();
+/// interpreter.set_limits(Limits {
+///     max_statements: Some(10_000),
+///     time_budget: Some(Duration::from_secs(1)),
+///     ..Limits::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Statements parsed in a file and evaluated by the interpreter
+    pub max_statements: Option<usize>,
+    /// Distinct identifiers in the unit of an expression
+    pub max_idents: Option<usize>,
+    /// Absolute value of the exponents, written or computed
+    pub max_exponent: Option<u32>,
+    /// Nesting of an expression, each operator and each parenthesis adding a level
+    pub max_depth: Option<usize>,
+    /// Wall-clock time of the parsing and the evaluation, counted from
+    /// [`Interpreter::set_limits`](crate::Interpreter::set_limits), or from the start of a
+    /// parsing done on its own with [`DigifyParser::parse_to_ast`](crate::DigifyParser::parse_to_ast)
+    pub time_budget: Option<Duration>,
+    /// Choices of exponents enumerated by a search, checked before it starts
+    pub max_search_candidates: Option<u64>,
+}
+
+/// Choices of exponents a search enumerates at most unless [`Limits`] says otherwise, about
+/// a second of work
+pub const DEFAULT_MAX_SEARCH_CANDIDATES: u64 = 10_000_000;
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_statements: None,
+            max_idents: None,
+            max_exponent: None,
+            max_depth: None,
+            time_budget: None,
+            max_search_candidates: Some(DEFAULT_MAX_SEARCH_CANDIDATES),
+        }
+    }
+}
+
+impl Limits {
+    pub(crate) fn check_statements(&self, count: usize, span: Span) -> Result<()> {
+        match self.max_statements {
+            Some(max) if count > max => Err(DigifyError::new(ErrorKind::StatementLimit(max), span)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_depth(&self, depth: usize, span: Span) -> Result<()> {
+        match self.max_depth {
+            Some(max) if depth > max => Err(DigifyError::new(ErrorKind::DepthLimit(max), span)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Instant at which the time budget starting now is spent
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.time_budget.map(|budget| Instant::now() + budget)
+    }
+
+    /// Error located at `span` once `deadline`, given by [`Self::deadline`], is past
+    pub(crate) fn check_time(&self, deadline: Option<Instant>, span: Span) -> Result<()> {
+        match (deadline, self.time_budget) {
+            (Some(deadline), Some(budget)) if Instant::now() > deadline => {
+                Err(DigifyError::new(ErrorKind::TimeLimit(budget), span))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the `candidates` a search would enumerate, `None` when they overflow
+    pub(crate) fn check_search(&self, candidates: Option<u64>, span: Span) -> Result<()> {
+        match (self.max_search_candidates, candidates) {
+            (Some(max), None) => Err(DigifyError::new(ErrorKind::SearchLimit(max), span)),
+            (Some(max), Some(candidates)) if candidates > max => {
+                Err(DigifyError::new(ErrorKind::SearchLimit(max), span))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the absolute value of an exponent
+    pub(crate) fn check_exponent(&self, magnitude: u32, span: Span) -> Result<()> {
+        match self.max_exponent {
+            Some(max) if magnitude > max => {
+                Err(DigifyError::new(ErrorKind::ExponentLimit(max), span))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the number of idents of `unit` and each of its exponents
+    pub(crate) fn check_unit(&self, unit: &Unit, span: Span) -> Result<()> {
+        if let Some(max) = self.max_idents {
+            if unit.iter().count() > max {
+                return Err(DigifyError::new(ErrorKind::IdentLimit(max), span));
+            }
+        }
+
+        unit.iter()
+            .try_for_each(|(_, exponent)| self.check_exponent(exponent.unsigned_abs(), span))
+    }
+}
@@ -1,44 +1,231 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, IsTerminal, Read};
+use std::process::ExitCode;
 
 use color_eyre::{
     config::HookBuilder,
-    eyre::{ensure, Context, Result},
+    eyre::{bail, eyre, Context, Report, Result},
 };
 
-mod error;
-mod interpreter;
-mod parser;
+use digify::{DigifyError, DigifyParser, Interpreter, Item, SourceMap, Span};
 
-use parser::DigifyParser;
+/// Why a command failed
+#[derive(Debug)]
+enum Failure {
+    /// Diagnostics already printed as JSON, nothing is left to say
+    Reported,
+    /// Error printed as the command ends: the count of the diagnostics, or an error outside
+    /// of the program like a missing file
+    Error(Report),
+}
 
-use crate::interpreter::Interpreter;
+impl From<Report> for Failure {
+    fn from(report: Report) -> Self {
+        Self::Error(report)
+    }
+}
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     HookBuilder::blank()
         .add_default_filters()
         .display_location_section(false)
-        .install()?;
+        .install()
+        .expect("the error hook is installed once");
 
     let args = std::env::args().skip(1);
     let (flags, args): (Vec<_>, Vec<_>) = args.partition(|arg| arg.starts_with('-'));
+    // An unknown format can only be reported as text
+    let (format, result) = match message_format(&flags) {
+        Ok(format) => (format, run(&args, &flags, format)),
+        Err(report) => (MessageFormat::Human, Err(report.into())),
+    };
 
-    ensure!(args.len() == 1, "No input file");
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Reported) => ExitCode::FAILURE,
+        Err(Failure::Error(report)) => {
+            match format {
+                MessageFormat::Human => eprintln!("Error: {:?}", report),
+                MessageFormat::Json => {
+                    eprintln!("{}", SourceMap::report_json_message("command", &report.to_string()))
+                }
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let mut input = String::new();
-    let mut file = File::open(&args[0]).wrap_err_with(|| format!("No file named: {}", args[1]))?;
-    file.read_to_string(&mut input)?;
+fn run(args: &[String], flags: &[String], format: MessageFormat) -> Result<(), Failure> {
+    if args.first().map(String::as_str) == Some("pi") {
+        if args.len() <= 2 {
+            return Err(eyre!("Usage: digify pi <file> <ident>...").into());
+        }
+        return pi(&args[1], &args[2..], flags, format);
+    }
+
+    if args.first().map(String::as_str) == Some("unit") {
+        if args.len() != 3 {
+            return Err(eyre!("Usage: digify unit <file> <expr>").into());
+        }
+        return unit(&args[1], &args[2], flags, format);
+    }
 
-    let input: &'static str = input.leak();
+    if args.len() != 1 {
+        return Err(eyre!("No input file").into());
+    }
 
-    let ast = DigifyParser::parse_to_ast(input)?;
+    let mut interpreter = interpreter(flags)?;
 
-    let mut interpreter = Interpreter::default();
+    let file = interpreter.sources_mut().add(&args[0], read_input(&args[0])?);
+    let ast = DigifyParser::parse_to_ast(interpreter.sources(), file, interpreter.limits())
+        .map_err(|errors| report_all(&interpreter, errors, format))?;
 
     if flags.contains(&"-d".to_string()) {
         dbg!(&ast);
     }
-    interpreter.eval(ast)?;
+    let program = interpreter.resolve(ast);
+    interpreter
+        .run(program)
+        .map_err(|errors| report_all(&interpreter, errors, format))?;
+
+    Ok(())
+}
+
+/// `digify pi <file> <ident>...`: dimensionless groups of variables declared in a file,
+/// without the prints of the file
+fn pi(
+    path: &str,
+    idents: &[String],
+    flags: &[String],
+    format: MessageFormat,
+) -> Result<(), Failure> {
+    let mut interpreter = interpreter(flags)?;
+    interpreter.set_output(io::sink());
+    load(&mut interpreter, path, format)?;
+
+    // The idents are located in the command line for the errors
+    let file = interpreter
+        .sources_mut()
+        .add("<command line>", idents.join(" "));
+    let mut start = 0;
+    let variables = idents
+        .iter()
+        .map(|ident| {
+            let span = Span::new(file, start, start + ident.len());
+            start = span.end() + 1;
+            Item::new(ident.as_str().into(), span)
+        })
+        .collect::<Vec<_>>();
+
+    let groups = interpreter
+        .pi_groups(&variables)
+        .map_err(|error| report(&interpreter, error, format))?;
+    for (i, group) in groups.iter().enumerate() {
+        println!("π{} = {}", i + 1, group);
+    }
 
     Ok(())
 }
+
+/// `digify unit <file> <expr>`: unit of an expression using the variables declared in a file,
+/// without the prints of the file
+fn unit(path: &str, expr: &str, flags: &[String], format: MessageFormat) -> Result<(), Failure> {
+    let mut interpreter = interpreter(flags)?;
+    interpreter.set_output(io::sink());
+    load(&mut interpreter, path, format)?;
+
+    let unit = interpreter
+        .parse_unit(expr)
+        .map_err(|error| report(&interpreter, error, format))?;
+    println!("{}", interpreter.render(&unit));
+
+    Ok(())
+}
+
+fn interpreter(flags: &[String]) -> Result<Interpreter<'static>> {
+    let mut interpreter = Interpreter::default();
+    if let Some(style) = flags.iter().find_map(|flag| flag.strip_prefix("--render=")) {
+        interpreter.set_renderer(style.parse()?);
+    }
+    interpreter.set_sort_definitions(flags.contains(&"--sort-definitions".to_string()));
+
+    Ok(interpreter)
+}
+
+/// How the diagnostics are printed
+#[derive(Debug, Clone, Copy)]
+enum MessageFormat {
+    /// The source lines with the spans underlined
+    Human,
+    /// A JSON object per line, see [`digify::SourceMap::report_json`]
+    Json,
+}
+
+fn message_format(flags: &[String]) -> Result<MessageFormat> {
+    let format = flags
+        .iter()
+        .find_map(|flag| flag.strip_prefix("--message-format="));
+
+    match format {
+        None | Some("human") => Ok(MessageFormat::Human),
+        Some("json") => Ok(MessageFormat::Json),
+        Some(format) => bail!("Unknown message format: {} (human or json)", format),
+    }
+}
+
+/// Evaluate the file at `path` in the global scope of `interpreter`
+fn load(interpreter: &mut Interpreter, path: &str, format: MessageFormat) -> Result<(), Failure> {
+    let file = interpreter.sources_mut().add(path, read_input(path)?);
+    let program = DigifyParser::parse_to_ast(interpreter.sources(), file, interpreter.limits())
+        .map_err(|errors| report_all(interpreter, errors, format))?;
+    let program = interpreter.resolve(program);
+
+    interpreter
+        .run(program)
+        .map_err(|errors| report_all(interpreter, errors, format))
+}
+
+/// Print `error` with the lines of the source where it happened, see [`report_all`]
+fn report(interpreter: &Interpreter, error: DigifyError, format: MessageFormat) -> Failure {
+    report_all(interpreter, vec![error], format)
+}
+
+/// Print every error of `errors` with the lines of the source where it happened, coloured on
+/// a terminal unless `NO_COLOR` is set. The failure returned only counts them.
+///
+/// In JSON, each error is a line of its own and nothing follows, as the count would not be
+/// JSON.
+fn report_all(
+    interpreter: &Interpreter,
+    errors: Vec<DigifyError>,
+    format: MessageFormat,
+) -> Failure {
+    if let MessageFormat::Json = format {
+        for error in &errors {
+            eprintln!("{}", interpreter.sources().report_json(error));
+        }
+        return Failure::Reported;
+    }
+
+    let colored = io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    for error in &errors {
+        let report = match colored {
+            true => interpreter.sources().report_colored(error),
+            false => interpreter.sources().report(error),
+        };
+        eprintln!("{}\n", report);
+    }
+
+    match errors.len() {
+        1 => eyre!("1 error").into(),
+        count => eyre!("{} errors", count).into(),
+    }
+}
+
+fn read_input(path: &str) -> Result<String> {
+    let mut input = String::new();
+    let mut file = File::open(path).wrap_err_with(|| format!("No file named: {}", path))?;
+    file.read_to_string(&mut input)?;
+
+    Ok(input)
+}
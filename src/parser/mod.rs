@@ -1,247 +1,345 @@
 use std::fmt;
-
-use color_eyre::eyre::{bail, Result};
+use std::rc::Rc;
+use std::time::Instant;
 
 use pest::{iterators::Pair, Parser};
 use pest_derive::Parser;
 
 mod span;
+mod syntax;
 
 pub use span::*;
 
+use crate::error::{DigifyError, ErrorKind, Result};
+use crate::limits::Limits;
+use crate::render::Renderer;
+use crate::source::{FileId, SourceMap};
+use crate::symbol::Symbol;
+
 #[derive(Parser)]
 #[grammar = "../grammar/grammar.pest"]
 pub struct DigifyParser;
 
-#[derive(Debug)]
-pub struct Item<'a, T: fmt::Debug> {
+/// Node of the syntax tree with the span of its source
+#[derive(Debug, Clone)]
+pub struct Item<T: fmt::Debug> {
     pub item: T,
-    pub span: Span<'a>,
+    pub span: Span,
 }
 
-pub type Istr<'a> = Item<'a, &'a str>;
-pub type IExpr<'a> = Item<'a, Expr<'a>>;
-pub type IStmt<'a> = Item<'a, Stmt<'a>>;
+pub type Istr = Item<Symbol>;
+pub type IExpr = Item<Expr>;
+pub type IStmt = Item<Stmt>;
 
+/// Statement of a program as written, its names are resolved by
+/// [`Interpreter::resolve`](crate::Interpreter::resolve)
 #[derive(Debug)]
-pub enum Stmt<'a> {
-    Definition(Istr<'a>, Istr<'a>),
-    Let(Istr<'a>, IExpr<'a>),
-    Assert(IExpr<'a>, IExpr<'a>),
-    Print(Element<'a>),
-    Block(Vec<IStmt<'a>>),
+pub enum Stmt {
+    Definition(Istr, Istr),
+    Let(Istr, IExpr),
+    Assert(IExpr, IExpr),
+    Print(Element, Option<Renderer>),
+    Solve(Istr, IExpr, IExpr),
+    Pi(Vec<Istr>),
+    Search(IExpr, Vec<Istr>, Option<u32>),
+    Prefer(Vec<Istr>),
+    Block(Vec<IStmt>),
 }
 
+/// Expression of a unit as written
 #[derive(Debug)]
-pub enum Expr<'a> {
-    Ident(Istr<'a>),
-    Mul(Box<IExpr<'a>>, Box<IExpr<'a>>),
-    Div(Box<IExpr<'a>>, Box<IExpr<'a>>),
-    Power(Box<IExpr<'a>>, i32),
-    Simplify(Box<IExpr<'a>>),
+pub enum Expr {
+    Ident(Istr),
+    Mul(Box<IExpr>, Box<IExpr>),
+    Div(Box<IExpr>, Box<IExpr>),
+    Power(Box<IExpr>, i32),
+    /// Simplify down to axioms, or to the given basis of units
+    Simplify(Box<IExpr>, Option<Vec<Istr>>),
     None,
 }
 
+/// What a `print` statement writes
 #[derive(Debug)]
-pub enum Element<'a> {
-    String(Istr<'a>),
-    Expr(IExpr<'a>),
+pub enum Element {
+    String(Item<Rc<str>>),
+    Expr(IExpr),
 }
 
 impl DigifyParser {
-    pub fn parse_to_ast(file: &str) -> Result<IStmt> {
-        let file = Self::parse(Rule::program, file)?.next().unwrap();
-
-        let span = Span::from(file.as_span());
-        let block = file
-            .into_inner()
-            .map(Self::parse_stmt)
-            .collect::<Result<Vec<_>>>()?;
-        let block = Stmt::Block(block);
-
-        Ok(Item::new(block, span))
+    /// Parse the program of `file` within `limits`, reporting every syntax error: the
+    /// parsing goes on after the statement of each one.
+    /// The other errors of the statements, like an exponent overflow, are all reported too.
+    /// The time budget of `limits` starts with this call.
+    pub fn parse_to_ast(
+        sources: &SourceMap,
+        file: FileId,
+        limits: &Limits,
+    ) -> std::result::Result<IStmt, Vec<DigifyError>> {
+        Self::parse_program(sources, file, limits, limits.deadline())
     }
 
-    fn parse_stmt(pair: Pair<Rule>) -> Result<IStmt> {
-        if Rule::stmt == pair.as_rule() {
-            let span = Span::from(pair.as_span());
-            let mut inner = pair.into_inner();
-            let keyword = inner.next().unwrap();
-
-            let stmt = match keyword.as_rule() {
-                Rule::keyword_define => {
-                    let ident = inner.next().unwrap();
-                    let string = inner.next().unwrap().into_inner().next().unwrap();
-
-                    let ident = Item::new_str(ident);
-                    let string = Item::new_str(string);
-
-                    // let ident = Item::new(ident.as_str(), Span::from(ident.as_span()));
-                    // let string = Item::new(string.as_str(), Span::from(string.as_span()));
-
-                    Stmt::Definition(ident, string)
-                }
-                Rule::keyword_let => {
-                    let ident = inner.next().unwrap();
-                    let expr = Self::parse_expr(inner.next().unwrap())?;
-
-                    let ident = Item::new_str(ident);
-
-                    Stmt::Let(ident, expr)
-                }
-                Rule::keyword_assert => {
-                    let expr1 = Self::parse_expr(inner.next().unwrap())?;
-                    let expr2 = Self::parse_expr(inner.next().unwrap())?;
-                    Stmt::Assert(expr1, expr2)
-                }
-                Rule::keyword_print => {
-                    let element = Self::parse_element(inner.next().unwrap())?;
-                    Stmt::Print(element)
-                }
-                _ => bail!("unkown rule in stmt match: {:?}", keyword.as_rule()),
-            };
+    /// Parse the program of `file`, stopping at the first statement met after `deadline`
+    pub(crate) fn parse_program(
+        sources: &SourceMap,
+        file: FileId,
+        limits: &Limits,
+        deadline: Option<Instant>,
+    ) -> std::result::Result<IStmt, Vec<DigifyError>> {
+        let input = sources.text(file);
+        let program = match Self::parse(Rule::program, input) {
+            Ok(mut pairs) => pairs.next().unwrap(),
+            Err(error) => return Err(vec![syntax::error(file, input, 0, error)]),
+        };
+
+        let span = Span::from_pest(file, program.as_span());
+        let mut block = Vec::new();
+        let mut errors = Vec::new();
+        for (i, pair) in program.into_inner().enumerate() {
+            let span = Span::from_pest(file, pair.as_span());
+            let checked = limits
+                .check_statements(i + 1, span)
+                .and_then(|_| limits.check_time(deadline, span));
+            if let Err(error) = checked {
+                errors.push(error);
+                break;
+            }
+            if pair.as_rule() == Rule::invalid {
+                errors.push(syntax::statement_error(file, input, span.start()..span.end()));
+                continue;
+            }
+            match Self::parse_stmt(file, limits, pair) {
+                Ok(stmt) => block.push(stmt),
+                Err(error) => errors.push(error),
+            }
+        }
 
-            Ok(Item::new(stmt, span))
+        if errors.is_empty() {
+            Ok(Item::new(Stmt::Block(block), span))
         } else {
-            bail!("Try parsing {:?} as a Stmt", pair.as_rule())
+            Err(errors)
         }
     }
 
-    fn parse_expr(pair: Pair<Rule>) -> Result<IExpr> {
-        if pair.as_rule() == Rule::expr {
-            let outer_span = Span::from(pair.as_span());
-            pair.into_inner()
-                .try_fold(Item::default(), move |acc, pair| {
-                    let expr = match pair.as_rule() {
-                        Rule::ident => {
-                            let span = Span::from(pair.as_span());
-                            let expr = Expr::Ident(Item::new_str(pair));
-                            let expr = Item::new(expr, span.clone());
-
-                            match acc.item {
-                                Expr::Mul(inner, _) => {
-                                    // let span = acc.span.extend(span);
-                                    let expr = Expr::Mul(inner, Box::new(expr));
-
-                                    Item::new(expr, acc.span)
-                                }
-                                Expr::Div(inner, _) => {
-                                    let expr = Expr::Div(inner, Box::new(expr));
-                                    // let span = acc.span.extend(span);
-
-                                    Item::new(expr, acc.span)
-                                }
-                                Expr::Simplify(_) => {
-                                    let expr = Expr::Simplify(Box::new(expr));
-                                    // let span = acc.span.extend(span);
-
-                                    Item::new(expr, acc.span)
-                                }
-                                _ => expr,
-                            }
-                        }
-                        Rule::expr => {
-                            let expr = Self::parse_expr(pair)?;
-
-                            match acc.item {
-                                Expr::Mul(inner, _) => {
-                                    let expr = Expr::Mul(inner, Box::new(expr));
-                                    // let span = acc.span.extend(span);
-
-                                    Item::new(expr, acc.span)
-                                }
-                                Expr::Div(inner, _) => {
-                                    let expr = Expr::Div(inner, Box::new(expr));
-                                    // let span = acc.span.extend(span);
-
-                                    Item::new(expr, acc.span)
-                                }
-                                Expr::Simplify(_) => {
-                                    let expr = Expr::Simplify(Box::new(expr));
-                                    // let span = acc.span.extend(span);
-
-                                    Item::new(expr, acc.span)
-                                }
-                                _ => expr,
-                            }
-                        }
-                        Rule::mul => {
-                            let expr = Expr::Mul(Box::new(acc), Box::default());
-                            // let span = Span::from(pair.as_span());
-
-                            Item::new(expr, outer_span.clone())
-                        }
-                        Rule::div => {
-                            let expr = Expr::Div(Box::new(acc), Box::default());
-                            // let span = Span::from(pair.as_span());
+    /// Parse `file` as a standalone expression, outside of any program, within `limits`
+    pub fn parse_unit(sources: &SourceMap, file: FileId, limits: &Limits) -> Result<IExpr> {
+        let input = sources.text(file);
+        let expr = Self::parse(Rule::unit, input)
+            .map_err(|error| syntax::error(file, input, 0, error))?
+            .next()
+            .unwrap();
 
-                            Item::new(expr, outer_span.clone())
-                        }
-                        Rule::number => {
-                            let expr = Expr::Power(
-                                Box::new(acc),
-                                pair.as_str().trim().parse::<i32>().unwrap(),
-                            );
-                            let span = Span::from(pair.as_span());
-
-                            Item::new(expr, span)
-                        }
-                        Rule::percent => {
-                            let expr = Expr::Simplify(Box::default());
-                            let span = Span::from(pair.as_span());
+        Self::parse_expr(file, limits, 1, expr)
+    }
 
-                            Item::new(expr, span)
+    fn parse_stmt(file: FileId, limits: &Limits, pair: Pair<Rule>) -> Result<IStmt> {
+        debug_assert_eq!(pair.as_rule(), Rule::stmt);
+        let span = Span::from_pest(file, pair.as_span());
+        let mut inner = pair
+            .into_inner()
+            .filter(|pair| !is_punctuation(pair.as_rule()));
+        let keyword = inner.next().unwrap();
+
+        let stmt = match keyword.as_rule() {
+            Rule::keyword_define => {
+                let ident = inner.next().unwrap();
+                let string = inner.next().unwrap().into_inner().next().unwrap();
+
+                let ident = Item::new_str(file, ident);
+                let string = Item::new_str(file, string);
+
+                Stmt::Definition(ident, string)
+            }
+            Rule::keyword_let => {
+                let ident = inner.next().unwrap();
+                let expr = Self::parse_expr(file, limits, 1, inner.next().unwrap())?;
+
+                let ident = Item::new_str(file, ident);
+
+                Stmt::Let(ident, expr)
+            }
+            Rule::keyword_assert => {
+                let expr1 = Self::parse_expr(file, limits, 1, inner.next().unwrap())?;
+                let expr2 = Self::parse_expr(file, limits, 1, inner.next().unwrap())?;
+                Stmt::Assert(expr1, expr2)
+            }
+            Rule::keyword_print => {
+                let element = Self::parse_element(file, limits, inner.next().unwrap())?;
+                let renderer = inner.find(|pair| pair.as_rule() == Rule::style).map(|pair| {
+                    pair.as_str()
+                        .parse()
+                        .expect("the grammar only lets through known styles")
+                });
+                Stmt::Print(element, renderer)
+            }
+            Rule::keyword_solve => {
+                let ident = Item::new_str(file, inner.next().unwrap());
+                let lhs = Self::parse_expr(file, limits, 1, inner.next().unwrap())?;
+                let rhs = Self::parse_expr(file, limits, 1, inner.next().unwrap())?;
+                Stmt::Solve(ident, lhs, rhs)
+            }
+            Rule::keyword_pi => {
+                let idents = inner.map(|pair| Item::new_str(file, pair)).collect();
+                Stmt::Pi(idents)
+            }
+            Rule::keyword_prefer => {
+                let idents = inner.map(|pair| Item::new_str(file, pair)).collect();
+                Stmt::Prefer(idents)
+            }
+            Rule::keyword_search => {
+                let target = Self::parse_expr(file, limits, 1, inner.next().unwrap())?;
+                let mut idents = Vec::new();
+                let mut bound = None;
+                for pair in inner {
+                    match pair.as_rule() {
+                        Rule::ident => idents.push(Item::new_str(file, pair)),
+                        Rule::bound => {
+                            let span = Span::from_pest(file, pair.as_span());
+                            let value = pair.as_str().parse::<u32>().map_err(|_| {
+                                DigifyError::new(ErrorKind::ExponentOverflow, span)
+                            })?;
+                            limits.check_exponent(value, span)?;
+                            bound = Some(value);
                         }
+                        Rule::keyword_with | Rule::keyword_max => (),
                         _ => unreachable!(),
-                    };
+                    }
+                }
+                Stmt::Search(target, idents, bound)
+            }
+            _ => unreachable!("every statement starts with a keyword"),
+        };
 
-                    Ok(expr)
-                })
-        } else {
-            bail!("Try parsing {:?} as a Expr", pair.as_rule())
-        }
+        Ok(Item::new(stmt, span))
     }
 
-    fn parse_element(pair: Pair<Rule>) -> Result<Element> {
-        if pair.as_rule() == Rule::element {
-            let inner = pair.into_inner().next().unwrap();
-
-            let element = match inner.as_rule() {
-                Rule::expr => Element::Expr(Self::parse_expr(inner)?),
-                Rule::string => {
-                    let string = inner.into_inner().next().unwrap();
-                    Element::String(Item::new_str(string))
-                }
-                _ => unreachable!(),
-            };
+    /// Parse an expression nested in `depth - 1` others
+    fn parse_expr(file: FileId, limits: &Limits, depth: usize, pair: Pair<Rule>) -> Result<IExpr> {
+        debug_assert!(matches!(pair.as_rule(), Rule::expr | Rule::expr_nj));
+        let outer_span = Span::from_pest(file, pair.as_span());
+        limits.check_depth(depth, outer_span)?;
+        pair.into_inner()
+            .try_fold(Item::default(), move |acc, pair| {
+                let operand = match pair.as_rule() {
+                    Rule::ident => {
+                        let span = Span::from_pest(file, pair.as_span());
+                        Item::new(Expr::Ident(Item::new_str(file, pair)), span)
+                    }
+                    Rule::expr | Rule::expr_nj => {
+                        Self::parse_expr(file, limits, depth + 1, pair)?
+                    }
+                    Rule::group => {
+                        let span = Span::from_pest(file, pair.as_span());
+                        let inner = pair.into_inner().next().unwrap();
+                        let expr = Self::parse_expr(file, limits, depth + 1, inner)?;
+
+                        Item::new(expr.item, span)
+                    }
+                    Rule::mul | Rule::juxt => {
+                        let span = acc.span.extend(Span::from_pest(file, pair.as_span()));
+                        return Ok(Item::new(Expr::Mul(Box::new(acc), Box::default()), span));
+                    }
+                    Rule::div => {
+                        let span = acc.span.extend(Span::from_pest(file, pair.as_span()));
+                        return Ok(Item::new(Expr::Div(Box::new(acc), Box::default()), span));
+                    }
+                    Rule::number => {
+                        let span = Span::from_pest(file, pair.as_span());
+                        // The grammar only lets through an optional minus sign followed by
+                        // digits, parsing fails on overflow
+                        let number = pair.as_str().parse::<i32>().map_err(|_| {
+                            DigifyError::new(ErrorKind::ExponentOverflow, span)
+                        })?;
+                        limits.check_exponent(number.unsigned_abs(), span)?;
+                        let span = acc.span.extend(span);
+
+                        return Ok(Item::new(Expr::Power(Box::new(acc), number), span));
+                    }
+                    Rule::one => Item::new(Expr::None, Span::from_pest(file, pair.as_span())),
+                    Rule::percent => {
+                        let span = Span::from_pest(file, pair.as_span());
+                        let basis = pair
+                            .into_inner()
+                            .filter(|pair| pair.as_rule() == Rule::ident)
+                            .map(|pair| Item::new_str(file, pair))
+                            .collect::<Vec<_>>();
+                        let basis = (!basis.is_empty()).then_some(basis);
+
+                        return Ok(Item::new(Expr::Simplify(Box::default(), basis), span));
+                    }
+                    _ => unreachable!(),
+                };
+
+                // The operand completes the operator before it, if any
+                let span = acc.span.extend(operand.span);
+                let expr = match acc.item {
+                    Expr::Mul(inner, _) => Item::new(Expr::Mul(inner, Box::new(operand)), span),
+                    Expr::Div(inner, _) => Item::new(Expr::Div(inner, Box::new(operand)), span),
+                    Expr::Simplify(_, basis) => {
+                        Item::new(Expr::Simplify(Box::new(operand), basis), span)
+                    }
+                    _ => operand,
+                };
+
+                Ok(expr)
+            })
+    }
 
-            Ok(element)
-        } else {
-            bail!("Try parsing {:?} as a Element", pair.as_rule())
-        }
+    fn parse_element(file: FileId, limits: &Limits, pair: Pair<Rule>) -> Result<Element> {
+        debug_assert_eq!(pair.as_rule(), Rule::element);
+        let inner = pair.into_inner().next().unwrap();
+
+        let element = match inner.as_rule() {
+            Rule::expr => Element::Expr(Self::parse_expr(file, limits, 1, inner)?),
+            Rule::string => {
+                let string = inner.into_inner().next().unwrap();
+                let span = Span::from_pest(file, string.as_span());
+                Element::String(Item::new(string.as_str().into(), span))
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(element)
     }
 }
 
-impl<'a, T: fmt::Debug> Item<'a, T> {
+/// Whether `rule` only matches punctuation, which the AST does not keep
+fn is_punctuation(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::semicolon
+            | Rule::assign
+            | Rule::equal
+            | Rule::colon
+            | Rule::comma
+            | Rule::close_paren
+            | Rule::close_bracket
+    )
+}
+
+impl<T: fmt::Debug> Item<T> {
+    pub fn new(item: T, span: Span) -> Self {
+        Self { item, span }
+    }
+
     pub fn item(&self) -> &T {
         &self.item
     }
 }
 
-impl<'a> Item<'a, &'a str> {
-    fn new_str(value: Pair<'a, Rule>) -> Self {
+impl Item<Symbol> {
+    fn new_str(file: FileId, value: Pair<Rule>) -> Self {
         Self {
-            item: value.as_str(),
-            span: Span::from(value.as_span()),
+            item: Symbol::intern(value.as_str()),
+            span: Span::from_pest(file, value.as_span()),
         }
     }
 
-    pub fn as_str(&self) -> &'a str {
-        self.item
+    pub fn as_str(&self) -> &'static str {
+        self.item.as_str()
     }
 }
 
-impl<'a> Default for Item<'a, Expr<'a>> {
+impl Default for Item<Expr> {
     fn default() -> Self {
         Self {
             item: Expr::None,
@@ -249,9 +347,3 @@ impl<'a> Default for Item<'a, Expr<'a>> {
         }
     }
 }
-
-impl<'a, T: fmt::Debug> Item<'a, T> {
-    pub fn new(item: T, span: Span<'a>) -> Self {
-        Self { item, span }
-    }
-}
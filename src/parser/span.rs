@@ -1,33 +1,52 @@
+use crate::source::FileId;
+
+/// Byte range `start..end` of a file of a [`SourceMap`](crate::SourceMap)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    file: FileId,
+    start: usize,
+    end: usize,
+}
 
+impl Span {
+    pub fn new(file: FileId, start: usize, end: usize) -> Self {
+        Self { file, start, end }
+    }
 
-#[derive(Debug, Default, Clone)]
-pub struct Span<'a> {
-    input: &'a str,
-    start: Position,
-    _end: Position,
-}
+    pub(crate) fn from_pest(file: FileId, span: pest::Span) -> Self {
+        Self::new(file, span.start(), span.end())
+    }
 
-impl<'a> Span<'a> {
-    pub fn start(&self) -> &Position {
-        &self.start
+    pub fn file(&self) -> FileId {
+        self.file
     }
 
-    pub fn _end(&self) -> &Position {
-        &self._end
+    pub fn start(&self) -> usize {
+        self.start
     }
 
-    pub fn input(&self) -> &str {
-        self.input
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Span covering both `self` and `other`, which are in the same file
+    pub fn extend(self, other: Span) -> Span {
+        Self::new(self.file, self.start.min(other.start), self.end.max(other.end))
     }
 }
 
-#[derive(Debug, Default, Clone)]
+/// Line and column in a file, both starting at 1
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Position {
     line: usize,
     col: usize,
 }
 
 impl Position {
+    pub(crate) fn new(line: usize, col: usize) -> Self {
+        Self { line, col }
+    }
+
     pub fn line(&self) -> usize {
         self.line
     }
@@ -36,24 +55,3 @@ impl Position {
         self.col
     }
 }
-
-impl<'a> From<pest::Span<'a>> for Span<'a> {
-    fn from(value: pest::Span<'a>) -> Self {
-        let start = value.start_pos().into();
-        let end = value.end_pos().into();
-
-        Self {
-            input: value.as_str(),
-            start,
-            _end: end,
-        }
-    }
-}
-
-impl From<pest::Position<'_>> for Position {
-    fn from(value: pest::Position) -> Self {
-        let (line, col) = value.line_col();
-        Self { line, col }
-    }
-}
-
@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+use pest::error::{Error, ErrorVariant, InputLocation};
+use pest::Parser;
+
+use super::{DigifyParser, Rule, Span};
+use crate::error::{DigifyError, ErrorKind};
+use crate::source::FileId;
+
+/// Syntax error of the statement of `input` at `range`, which the grammar only matched as
+/// invalid text. The statement is parsed again on its own, so that the cost of the errors
+/// stays linear in the size of the input.
+pub fn statement_error(file: FileId, input: &str, range: Range<usize>) -> DigifyError {
+    match DigifyParser::parse(Rule::stmt, &input[range.clone()]) {
+        Err(error) => self::error(file, input, range.start, error),
+        // A statement is always tried before invalid text, this is only a safe fallback
+        Ok(_) => {
+            let found = token(input, range.start).unwrap_or_default();
+            let kind = ErrorKind::UnexpectedToken {
+                expected: vec!["a statement".to_owned()],
+                found: format!("`{}`", found),
+            };
+            DigifyError::new(kind, Span::new(file, range.start, range.start + found.len()))
+        }
+    }
+}
+
+/// Syntax error of `input` parsed from `offset`, telling what was found and what could have
+/// been
+pub fn error(file: FileId, input: &str, offset: usize, error: Error<Rule>) -> DigifyError {
+    let start = offset + position(&error);
+    let (positives, negatives) = match error.variant {
+        ErrorVariant::ParsingError {
+            positives,
+            negatives,
+        } => (positives, negatives),
+        ErrorVariant::CustomError { message } => {
+            // Like the call limit of pest
+            let span = Span::new(file, start, start);
+            return DigifyError::new(ErrorKind::Syntax(message), span);
+        }
+    };
+
+    let found = token(input, start);
+    let span = Span::new(file, start, start + found.map_or(0, str::len));
+
+    let kind = match found {
+        Some(word) if positives.is_empty() && negatives.contains(&Rule::reserved) => {
+            ErrorKind::ReservedIdent(word.to_owned())
+        }
+        Some("\"") if !input[start + 1..].contains('"') => ErrorKind::UnterminatedString,
+        _ => {
+            let mut expected = Vec::new();
+            for description in positives.iter().filter_map(|rule| describe(*rule)) {
+                if !expected.contains(&description) {
+                    expected.push(description);
+                }
+            }
+
+            let found = match found {
+                Some(token) => format!("`{}`", token),
+                None => "the end of the file".to_owned(),
+            };
+            let expected = expected.into_iter().map(str::to_owned).collect();
+            ErrorKind::UnexpectedToken { expected, found }
+        }
+    };
+
+    DigifyError::new(kind, span)
+}
+
+fn position(error: &Error<Rule>) -> usize {
+    match error.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    }
+}
+
+/// Token starting at `start`: a word, `==` or a single character, `None` at the end
+fn token(input: &str, start: usize) -> Option<&str> {
+    let rest = &input[start..];
+    let first = rest.chars().next()?;
+    let length = if first.is_ascii_alphanumeric() || first == '_' {
+        rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len())
+    } else if rest.starts_with("==") {
+        2
+    } else {
+        first.len_utf8()
+    };
+
+    Some(&rest[..length])
+}
+
+/// What a rule stands for in a message, `None` for the rules inside the others
+fn describe(rule: Rule) -> Option<&'static str> {
+    let description = match rule {
+        Rule::ident => "an identifier",
+        Rule::expr | Rule::expr_nj | Rule::juxt | Rule::group => "an expression",
+        Rule::element => "a string or an expression",
+        Rule::string => "a string",
+        Rule::number => "an exponent",
+        Rule::bound => "a number",
+        Rule::one => "`1`",
+        Rule::percent => "`%`",
+        Rule::mul => "`*`",
+        Rule::div => "`/`",
+        Rule::style => "a style (ascii, unicode, fraction, latex, siunitx or canonical)",
+        Rule::stmt => "a statement",
+        Rule::EOI => "the end of the file",
+        Rule::keyword_define => "`define`",
+        Rule::keyword_let => "`let`",
+        Rule::keyword_assert => "`assert`",
+        Rule::keyword_print => "`print`",
+        Rule::keyword_solve => "`solve`",
+        Rule::keyword_pi => "`pi`",
+        Rule::keyword_search => "`search`",
+        Rule::keyword_prefer => "`prefer`",
+        Rule::keyword_as => "`as`",
+        Rule::keyword_with => "`with`",
+        Rule::keyword_max => "`max`",
+        Rule::semicolon => "`;`",
+        Rule::assign => "`=`",
+        Rule::equal => "`==`",
+        Rule::colon => "`:`",
+        Rule::comma => "`,`",
+        Rule::close_paren => "`)`",
+        Rule::close_bracket => "`]`",
+        _ => return None,
+    };
+
+    Some(description)
+}
@@ -0,0 +1,218 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Style used to print units
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    /// `m s-2`
+    #[default]
+    Ascii,
+    /// `m·s⁻²`
+    Unicode,
+    /// `m/s²`
+    Fraction,
+    /// `\mathrm{m}\,\mathrm{s}^{-2}`
+    Latex,
+    /// `\si{\metre\per\second\squared}`
+    Siunitx,
+    /// `D T-2`, with idents instead of symbols so that it can be parsed back
+    Canonical,
+}
+
+/// Factor of a printed unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Factor<'a> {
+    pub name: &'a str,
+    pub power: i32,
+    /// Whether `name` is the symbol of an axiom, like `m`, rather than the ident of an other
+    /// unit, like `Vit`
+    pub symbol: bool,
+}
+
+impl Renderer {
+    /// Render `factors`, the symbols of the units paired with their exponent,
+    /// positive exponents first
+    pub fn render(&self, factors: &[(&str, i32)]) -> String {
+        let factors = factors
+            .iter()
+            .map(|(name, power)| Factor {
+                name,
+                power: *power,
+                symbol: true,
+            })
+            .collect::<Vec<_>>();
+
+        self.render_factors(&factors)
+    }
+
+    /// Render `factors`, positive exponents first. Only the symbols are written with
+    /// siunitx macros, the idents are written as text.
+    pub(crate) fn render_factors(&self, factors: &[Factor]) -> String {
+        if factors.is_empty() {
+            return match self {
+                Self::Siunitx => "\\si{1}".to_string(),
+                _ => "1".to_string(),
+            };
+        }
+
+        match self {
+            Self::Ascii | Self::Canonical => join(factors, " ", |name, power| match power {
+                1 => name.to_string(),
+                _ => format!("{}{}", name, power),
+            }),
+            Self::Unicode => join(factors, "·", unicode),
+            Self::Fraction => {
+                let top = factors
+                    .iter()
+                    .filter(|factor| factor.power > 0)
+                    .copied()
+                    .collect::<Vec<_>>();
+                let bottom = factors
+                    .iter()
+                    .filter(|factor| factor.power < 0)
+                    .map(|factor| Factor {
+                        power: -factor.power,
+                        ..*factor
+                    })
+                    .collect::<Vec<_>>();
+
+                let top = if top.is_empty() {
+                    "1".to_string()
+                } else {
+                    join(&top, "·", unicode)
+                };
+
+                match bottom.len() {
+                    0 => top,
+                    1 => format!("{}/{}", top, join(&bottom, "·", unicode)),
+                    _ => format!("{}/({})", top, join(&bottom, "·", unicode)),
+                }
+            }
+            Self::Latex => join(factors, "\\,", |name, power| match power {
+                1 => format!("\\mathrm{{{}}}", name),
+                _ => format!("\\mathrm{{{}}}^{{{}}}", name, power),
+            }),
+            Self::Siunitx => {
+                let units = factors.iter().map(siunitx_factor).collect::<String>();
+
+                format!("\\si{{{}}}", units)
+            }
+        }
+    }
+}
+
+/// Name of a style that does not exist, given to [`Renderer::from_str`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRendererError(String);
+
+impl fmt::Display for ParseRendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown render style: {}", self.0)
+    }
+}
+
+impl Error for ParseRendererError {}
+
+impl FromStr for Renderer {
+    type Err = ParseRendererError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(Self::Ascii),
+            "unicode" => Ok(Self::Unicode),
+            "fraction" => Ok(Self::Fraction),
+            "latex" => Ok(Self::Latex),
+            "siunitx" => Ok(Self::Siunitx),
+            "canonical" => Ok(Self::Canonical),
+            _ => Err(ParseRendererError(s.to_owned())),
+        }
+    }
+}
+
+fn join(factors: &[Factor], separator: &str, factor: impl Fn(&str, i32) -> String) -> String {
+    factors
+        .iter()
+        .map(|Factor { name, power, .. }| factor(name, *power))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn unicode(name: &str, power: i32) -> String {
+    if power == 1 {
+        return name.to_string();
+    }
+
+    let power = power
+        .to_string()
+        .chars()
+        .map(|c| match c {
+            '-' => '⁻',
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            _ => '⁹',
+        })
+        .collect::<String>();
+
+    format!("{}{}", name, power)
+}
+
+/// siunitx macros of a factor, an ident is written as text
+fn siunitx_factor(factor: &Factor) -> String {
+    let per = if factor.power < 0 { "\\per" } else { "" };
+    let power = match factor.power.abs() {
+        1 => String::new(),
+        2 => "\\squared".to_string(),
+        3 => "\\cubed".to_string(),
+        power => format!("\\tothe{{{}}}", power),
+    };
+    let name = match factor.symbol {
+        true => siunitx(factor.name),
+        false => format!("\\text{{{}}}", factor.name),
+    };
+
+    format!("{}{}{}", per, name, power)
+}
+
+/// siunitx macro of a SI symbol, other names are kept as is
+fn siunitx(name: &str) -> String {
+    let unit = match name {
+        "m" => "metre",
+        "s" => "second",
+        "g" => "gram",
+        "kg" => "kilogram",
+        "A" => "ampere",
+        "K" => "kelvin",
+        "mol" => "mole",
+        "cd" => "candela",
+        "N" => "newton",
+        "J" => "joule",
+        "W" => "watt",
+        "Pa" => "pascal",
+        "Hz" => "hertz",
+        "C" => "coulomb",
+        "V" => "volt",
+        "F" => "farad",
+        "Ω" => "ohm",
+        "S" => "siemens",
+        "Wb" => "weber",
+        "T" => "tesla",
+        "H" => "henry",
+        "lm" => "lumen",
+        "lx" => "lux",
+        "Bq" => "becquerel",
+        "Gy" => "gray",
+        "Sv" => "sievert",
+        "kat" => "katal",
+        _ => return name.to_string(),
+    };
+
+    format!("\\{}", unit)
+}
@@ -0,0 +1,398 @@
+use crate::error::DigifyError;
+use crate::parser::{Position, Span};
+
+/// Identifier of a file added to a [`SourceMap`]. The slot of a removed file is reused with
+/// an other generation, so that an old id never refers to the new file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId {
+    index: u32,
+    generation: u32,
+}
+
+/// Owner of the text of every parsed file, the spans refer to them by [`FileId`]
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    slots: Vec<Slot>,
+    /// Indices of the slots without a file
+    free: Vec<u32>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    generation: u32,
+    file: Option<SourceFile>,
+}
+
+#[derive(Debug)]
+struct SourceFile {
+    name: String,
+    text: String,
+    /// Byte offset of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    /// Add a file named `name`, like a path or `<input>`, and return its id
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<String>) -> FileId {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        let file = Some(SourceFile {
+            name: name.into(),
+            text,
+            line_starts,
+        });
+
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.generation = slot.generation.wrapping_add(1);
+                slot.file = file;
+                FileId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("too many files");
+                self.slots.push(Slot {
+                    generation: 0,
+                    file,
+                });
+                FileId {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Drop the text of `file`, returning whether it was in the map. The spans in it are not
+    /// [contained](Self::contains) in the map anymore: the reports leave out their lines.
+    pub fn remove(&mut self, file: FileId) -> bool {
+        let removed = self
+            .slots
+            .get_mut(file.index as usize)
+            .filter(|slot| slot.generation == file.generation)
+            .and_then(|slot| slot.file.take())
+            .is_some();
+        if removed {
+            self.free.push(file.index);
+        }
+
+        removed
+    }
+
+    /// # Panics
+    ///
+    /// If `file` is not in the map, like after its [removal](Self::remove).
+    pub fn name(&self, file: FileId) -> &str {
+        &self.file(file).name
+    }
+
+    /// # Panics
+    ///
+    /// If `file` is not in the map, like after its [removal](Self::remove).
+    pub fn text(&self, file: FileId) -> &str {
+        &self.file(file).text
+    }
+
+    fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.slots
+            .get(file.index as usize)
+            .filter(|slot| slot.generation == file.generation)
+            .and_then(|slot| slot.file.as_ref())
+    }
+
+    fn file(&self, file: FileId) -> &SourceFile {
+        self.get(file).expect("the file is not in the source map")
+    }
+
+    /// Text covered by `span`
+    pub fn slice(&self, span: Span) -> &str {
+        &self.text(span.file())[span.start()..span.end()]
+    }
+
+    /// Line and column of the byte `offset` of `file`
+    pub fn position(&self, file: FileId, offset: usize) -> Position {
+        let file = self.file(file);
+        let line = file.line_starts.partition_point(|start| *start <= offset);
+        let start = file.line_starts[line - 1];
+        let col = file.text[start..offset].chars().count() + 1;
+
+        Position::new(line, col)
+    }
+
+    /// Text of the line `line` of `file`, starting at 1, without its line break
+    pub fn line(&self, file: FileId, line: usize) -> &str {
+        let file = self.file(file);
+        let start = file.line_starts[line - 1];
+        let end = file.line_starts.get(line).map_or(file.text.len(), |end| end - 1);
+
+        file.text[start..end].trim_end_matches('\r')
+    }
+
+    /// Whether `span` is in a file of the map
+    pub fn contains(&self, span: Span) -> bool {
+        self.get(span.file())
+            .is_some_and(|file| span.start() <= span.end() && span.end() <= file.text.len())
+    }
+
+    /// Message of `error` followed by the lines where it happened, its span underlined with
+    /// `^` and its labels with `-`:
+    ///
+    /// ```text
+    /// error: Variable D already declared
+    ///  --> units.dgf:3:8
+    ///   |
+    /// 1 | define D "m";
+    ///   |        - previously declared here
+    /// ...
+    /// 3 | define D "s";
+    ///   |        ^ declared again here
+    /// ```
+    ///
+    /// The names suggested instead of the one of the error follow, like
+    /// `= help: did you mean `Vit`?`, then the notes of its kind, like `= note: ...`.
+    pub fn report(&self, error: &DigifyError) -> String {
+        self.diagnostic(error, &Palette::PLAIN)
+    }
+
+    /// [`Self::report`] with the colours of a terminal
+    pub fn report_colored(&self, error: &DigifyError) -> String {
+        self.diagnostic(error, &Palette::COLORED)
+    }
+
+    /// `error` as a JSON object on a single line, for the tools reading the diagnostics:
+    ///
+    /// ```text
+    /// {"kind":"variable_not_declared","level":"error","message":"Variable Vt not declared",
+    ///  "file":"units.dgf","start":{"line":4,"column":7},"end":{"line":4,"column":9},
+    ///  "label":"not declared","related":[],"suggestions":["Vit"],"notes":[]}
+    /// ```
+    ///
+    /// The `related` spans are objects with a `file`, a `start`, an `end` and a `message`.
+    /// A span outside of the map has a `null` file, start and end. Columns count characters
+    /// from 1, and `end` is just after the span.
+    pub fn report_json(&self, error: &DigifyError) -> String {
+        let strings = |strings: &[String]| {
+            let strings = strings.iter().map(|text| json_string(text)).collect::<Vec<_>>();
+            format!("[{}]", strings.join(","))
+        };
+        let related = error
+            .labels()
+            .iter()
+            .map(|label| {
+                let message = json_string(&label.message);
+                format!("{{{},\"message\":{}}}", self.json_location(label.span), message)
+            })
+            .collect::<Vec<_>>();
+        let label = error
+            .kind()
+            .label()
+            .map_or_else(|| "null".to_owned(), |label| json_string(&label));
+
+        format!(
+            "{{\"kind\":\"{}\",\"level\":\"error\",\"message\":{},{},\"label\":{},\
+             \"related\":[{}],\"suggestions\":{},\"notes\":{}}}",
+            error.kind().code(),
+            json_string(&error.to_string()),
+            self.json_location(error.span()),
+            label,
+            related.join(","),
+            strings(error.suggestions()),
+            strings(&error.kind().notes()),
+        )
+    }
+
+    /// Error outside of the sources, like a file that can not be read, as a JSON object with
+    /// the fields of [`Self::report_json`], its location being `null`
+    pub fn report_json_message(kind: &str, message: &str) -> String {
+        format!(
+            "{{\"kind\":{},\"level\":\"error\",\"message\":{},\"file\":null,\"start\":null,\
+             \"end\":null,\"label\":null,\"related\":[],\"suggestions\":[],\"notes\":[]}}",
+            json_string(kind),
+            json_string(message),
+        )
+    }
+
+    /// `"file"`, `"start"` and `"end"` fields of `span`
+    fn json_location(&self, span: Span) -> String {
+        if !self.contains(span) {
+            return "\"file\":null,\"start\":null,\"end\":null".to_owned();
+        }
+
+        let position = |offset| {
+            let position = self.position(span.file(), offset);
+            format!("{{\"line\":{},\"column\":{}}}", position.line(), position.col())
+        };
+        format!(
+            "\"file\":{},\"start\":{},\"end\":{}",
+            json_string(self.name(span.file())),
+            position(span.start()),
+            position(span.end())
+        )
+    }
+
+    fn diagnostic(&self, error: &DigifyError, palette: &Palette) -> String {
+        let Palette {
+            primary,
+            secondary,
+            bold,
+            reset,
+        } = palette;
+        let mut report = format!("{primary}error{reset}{bold}: {error}{reset}");
+
+        let primary_label = (error.span(), error.kind().label().unwrap_or_default(), true);
+        let labels = std::iter::once(primary_label)
+            .chain(
+                error
+                    .labels()
+                    .iter()
+                    .map(|label| (label.span, label.message.clone(), false)),
+            )
+            .filter(|(span, _, _)| self.contains(*span))
+            .map(|(span, message, is_primary)| {
+                let start = self.position(span.file(), span.start());
+                (span, start, message, is_primary)
+            })
+            .collect::<Vec<_>>();
+
+        let width = labels
+            .iter()
+            .map(|(_, start, _, _)| start.line().to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(width);
+
+        // The files in the order of their first label, the one of the error first
+        let mut files = Vec::new();
+        for (span, ..) in &labels {
+            if !files.contains(&span.file()) {
+                files.push(span.file());
+            }
+        }
+
+        for (i, file) in files.into_iter().enumerate() {
+            let mut labels = labels
+                .iter()
+                .filter(|(span, ..)| span.file() == file)
+                .collect::<Vec<_>>();
+            labels.sort_by_key(|(_, start, _, is_primary)| (start.line(), start.col(), !is_primary));
+
+            // The header of the file of the error locates the error itself
+            let (_, first, _, _) = labels
+                .iter()
+                .find(|(.., is_primary)| *is_primary)
+                .unwrap_or(&labels[0]);
+            let arrow = if i == 0 { "-->" } else { ":::" };
+            report += &format!(
+                "\n{gutter}{secondary}{arrow}{reset} {}:{}:{}",
+                self.name(file),
+                first.line(),
+                first.col()
+            );
+            report += &format!("\n{gutter} {secondary}|{reset}");
+
+            let mut previous_line = None;
+            for (span, start, message, is_primary) in labels {
+                let line = start.line();
+                let text = self.line(file, line);
+                if previous_line != Some(line) {
+                    if previous_line.is_some_and(|previous| line > previous + 1) {
+                        report += &format!("\n{secondary}...{reset}");
+                    }
+                    let text = text.replace('\t', " ");
+                    report += &format!("\n{secondary}{line:>width$} |{reset} {text}");
+                    previous_line = Some(line);
+                }
+
+                // A span over several lines is underlined up to the end of its first one
+                let rest = text.chars().count().saturating_sub(start.col() - 1);
+                let length = self.slice(*span).chars().count().min(rest).max(1);
+                let (marker, color) = if *is_primary {
+                    ("^", primary)
+                } else {
+                    ("-", secondary)
+                };
+                let message = if message.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", message)
+                };
+                report += &format!(
+                    "\n{gutter} {secondary}|{reset} {}{color}{}{message}{reset}",
+                    " ".repeat(start.col() - 1),
+                    marker.repeat(length)
+                );
+            }
+        }
+
+        if let Some((last, others)) = error.suggestions().split_last() {
+            let quote = |name: &String| format!("`{}`", name);
+            let suggestions = match others {
+                [] => quote(last),
+                _ => {
+                    let others = others.iter().map(quote).collect::<Vec<_>>();
+                    format!("{} or {}", others.join(", "), quote(last))
+                }
+            };
+            report += &format!("\n{gutter} {bold}= help{reset}: did you mean {}?", suggestions);
+        }
+
+        for note in error.kind().notes() {
+            // The next lines of a note are aligned after its `= note: `
+            let indent = format!("\n{gutter}         ");
+            let note = note.replace('\n', &indent);
+            report += &format!("\n{gutter} {bold}= note{reset}: {}", note);
+        }
+
+        report
+    }
+}
+
+/// `text` as a JSON string, quoted and escaped
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+/// Escape codes of the parts of a report
+struct Palette {
+    primary: &'static str,
+    secondary: &'static str,
+    bold: &'static str,
+    reset: &'static str,
+}
+
+impl Palette {
+    const PLAIN: Self = Self {
+        primary: "",
+        secondary: "",
+        bold: "",
+        reset: "",
+    };
+
+    const COLORED: Self = Self {
+        primary: "\x1b[1;31m",
+        secondary: "\x1b[1;34m",
+        bold: "\x1b[1m",
+        reset: "\x1b[0m",
+    };
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// Interned identifier, compared and hashed as an integer
+///
+/// Symbols are ordered by creation, use [`Symbol::as_str`] to order them by name.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// Symbol of each interned name, locked only to create a symbol
+#[derive(Default)]
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    /// Number of symbols created, interned or fresh
+    count: u32,
+}
+
+/// Name of each symbol, the chunk `i` holding the names of the symbols `2^i - 1..2^(i+1) - 1`.
+/// A chunk never moves once allocated, so that names are read without locking the interner.
+type Chunk = Box<[OnceLock<&'static str>]>;
+static NAMES: [OnceLock<Chunk>; 32] = [const { OnceLock::new() }; 32];
+
+fn interner() -> &'static Mutex<Interner> {
+    static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Mutex::default)
+}
+
+/// Chunk of the name of the symbol `id`, and its index in the chunk
+fn locate(id: u32) -> (usize, usize) {
+    let position = id as u64 + 1;
+    let chunk = position.ilog2();
+
+    (chunk as usize, (position - (1 << chunk)) as usize)
+}
+
+impl Interner {
+    fn create(&mut self, name: &'static str) -> Symbol {
+        let symbol = Symbol(self.count);
+        self.count = self.count.checked_add(1).expect("too many symbols");
+
+        let (chunk, index) = locate(symbol.0);
+        let chunk = NAMES[chunk].get_or_init(|| (0..1 << chunk).map(|_| OnceLock::new()).collect());
+        chunk[index].set(name).expect("each symbol is created once");
+
+        symbol
+    }
+}
+
+impl Symbol {
+    /// Symbol of `name`, the same for every call with an equal name
+    pub fn intern(name: &str) -> Self {
+        let mut interner = interner().lock().unwrap();
+        if let Some(symbol) = interner.symbols.get(name) {
+            return *symbol;
+        }
+
+        // Names live as long as the program, like their symbols that may be copied anywhere
+        let name: &'static str = Box::leak(name.into());
+        let symbol = interner.create(name);
+        interner.symbols.insert(name, symbol);
+
+        symbol
+    }
+
+    /// New symbol named `name`, distinct from every other symbol even if they have this name.
+    /// It gives its own identity to a variable shadowing an other one, and shares the
+    /// stored name of the interned symbol.
+    pub fn fresh(name: &str) -> Self {
+        let name = Self::intern(name).as_str();
+
+        interner().lock().unwrap().create(name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        let (chunk, index) = locate(self.0);
+
+        NAMES[chunk]
+            .get()
+            .and_then(|chunk| chunk[index].get())
+            .expect("symbols are only created by the interner")
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self::intern(name)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
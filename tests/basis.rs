@@ -0,0 +1,20 @@
+//! A unit rewritten in the basis of a `%[...]` is printed with the units of the basis.
+
+mod common;
+
+use common::output;
+
+#[test]
+fn a_lone_basis_unit_is_not_expanded() {
+    assert_eq!(output("let E = M D2 T-2; print %[E] E;"), "E\n");
+    assert_eq!(output("let F = M D T-2; print %[F, D, T] F;"), "F\n");
+    // Without a basis the alias is printed with its definition
+    assert_eq!(output("let F = M D T-2; print F;"), "m kg s-2\n");
+}
+
+#[test]
+fn preferred_units_do_not_replace_the_basis() {
+    let program = "let F = M D T-2; let E = F D; prefer E; print %[F, D, T] F D;";
+
+    assert_eq!(output(program), "m F\n");
+}
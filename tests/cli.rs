@@ -0,0 +1,60 @@
+//! The `digify` command: its exit code and what it prints on errors.
+
+use std::process::{Command, Output};
+
+fn digify(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_digify"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn missing_file_is_a_json_record() {
+    let output = digify(&["--message-format=json", "no such file.dgf"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "{\"kind\":\"command\",\"level\":\"error\",\"message\":\"No file named: no such file.dgf\",\
+         \"file\":null,\"start\":null,\"end\":null,\"label\":null,\"related\":[],\
+         \"suggestions\":[],\"notes\":[]}\n"
+    );
+}
+
+#[test]
+fn missing_file_in_human_format() {
+    let output = digify(&["no such file.dgf"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.starts_with("Error:"));
+    assert!(stderr.contains("No file named: no such file.dgf"));
+}
+
+#[test]
+fn unknown_render_style_is_a_json_record() {
+    let output = digify(&["--message-format=json", "--render=fancy", "examples/example.dgf"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(stderr.starts_with("{\"kind\":\"command\""));
+    assert!(stderr.contains("Unknown render style: fancy"));
+}
+
+#[test]
+fn errors_of_the_program_are_json_records() {
+    let output = digify(&[
+        "--message-format=json",
+        "examples/errors/already_declared_variable.dgf",
+    ]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr
+        .lines()
+        .all(|line| line.starts_with("{\"kind\":\"variable_already_declared\"")));
+}
@@ -0,0 +1,46 @@
+//! The evaluation goes on after a failing statement, skipping the ones using its variable.
+
+mod common;
+
+use digify::{ErrorKind, Interpreter, Limits};
+
+#[test]
+fn errors_are_collected_and_bindings_poisoned() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    let program = common::resolve(
+        &mut interpreter,
+        "define D \"m\"; define T \"s\";
+         solve X: X X == D; let Y = X T; print Y; print %(X Y);
+         assert D T; print D T-1; let Z = D;",
+    );
+    assert!(program.errors().is_empty());
+
+    let errors = interpreter.run(program).unwrap_err();
+    assert!(matches!(errors[0].kind(), ErrorKind::NoSolution(..)));
+    assert!(matches!(errors[1].kind(), ErrorKind::AssertFail(..)));
+    assert_eq!(errors.len(), 2);
+
+    // The statements after the errors are evaluated, except those using X or Y
+    assert!(interpreter.lookup("Z").is_some());
+    assert!(interpreter.lookup("Y").is_none());
+    drop(interpreter);
+    assert_eq!(String::from_utf8(output).unwrap(), "m s-1\n");
+}
+
+#[test]
+fn limits_stop_the_evaluation() {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_limits(Limits {
+        max_statements: Some(2),
+        ..Limits::default()
+    });
+
+    let error = interpreter
+        .eval_str("define D \"m\"; define T \"s\"; let A = D; let B = T;")
+        .unwrap_err();
+
+    assert!(matches!(error.kind(), ErrorKind::StatementLimit(2)));
+    assert!(interpreter.lookup("B").is_none());
+}
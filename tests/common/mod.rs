@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests, each test file using a part of them.
+
+#![allow(dead_code)]
+
+use digify::{DigifyError, DigifyParser, IStmt, Interpreter, Program};
+
+/// Axioms declared before the programs of [`output`]
+pub const AXIOMS: &str = "define D \"m\"; define T \"s\"; define M \"kg\";";
+
+/// What `program` prints, evaluated after [`AXIOMS`]
+pub fn output(program: &str) -> String {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    interpreter.eval_str(AXIOMS).unwrap();
+    interpreter.eval_str(program).unwrap();
+    drop(interpreter);
+
+    String::from_utf8(output).unwrap()
+}
+
+/// Add `program` to the sources of `interpreter` as the file `name` and parse it
+pub fn parse(
+    interpreter: &mut Interpreter,
+    name: &str,
+    program: &str,
+) -> Result<IStmt, Vec<DigifyError>> {
+    let file = interpreter.sources_mut().add(name, program);
+
+    DigifyParser::parse_to_ast(interpreter.sources(), file, interpreter.limits())
+}
+
+/// Resolve the names of `program`, which has no syntax error
+pub fn resolve(interpreter: &mut Interpreter, program: &str) -> Program {
+    let program = parse(interpreter, "<test>", program).unwrap();
+
+    interpreter.resolve(program)
+}
+
+/// Parse, resolve and run `program` as the file `name`, with every error on the way
+pub fn run(
+    interpreter: &mut Interpreter,
+    name: &str,
+    program: &str,
+) -> Result<(), Vec<DigifyError>> {
+    let program = parse(interpreter, name, program)?;
+    let program = interpreter.resolve(program);
+
+    interpreter.run(program)
+}
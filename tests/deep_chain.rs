@@ -0,0 +1,45 @@
+//! Very long chains of definitions, each unit defined with the previous one, must be
+//! simplified and printed without overflowing the stack.
+
+use std::fs;
+use std::process::Command;
+
+const LENGTH: usize = 100_000;
+
+/// Ident made of letters only, as digits are read as exponents
+fn name(mut index: usize) -> String {
+    let mut name = String::from("U");
+    loop {
+        name.push((b'a' + (index % 26) as u8) as char);
+        index /= 26;
+        if index == 0 {
+            return name;
+        }
+    }
+}
+
+#[test]
+fn deep_chain_is_simplified() {
+    let mut program = String::from("define D \"m\";\nlet Ua = D;\n");
+    for i in 1..LENGTH {
+        program.push_str(&format!("let {} = {};\n", name(i), name(i - 1)));
+    }
+    let last = name(LENGTH - 1);
+    program.push_str(&format!("assert {} D;\nprint {};\nprint %{};\n", last, last, last));
+
+    let path = std::env::temp_dir().join("digify_deep_chain.dgf");
+    fs::write(&path, program).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_digify"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), ["m", "m"]);
+}
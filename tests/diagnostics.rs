@@ -0,0 +1,93 @@
+//! Reports of errors, with the source lines, their underlined spans and labels.
+
+mod common;
+
+use digify::Interpreter;
+
+fn report(program: &str) -> String {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(std::io::sink());
+    let error = common::run(&mut interpreter, "test.dgf", program)
+        .unwrap_err()
+        .swap_remove(0);
+
+    interpreter.sources().report(&error)
+}
+
+#[test]
+fn redeclaration_points_at_both_declarations() {
+    let report = report("define D \"m\";\ndefine T \"s\";\ndefine D \"s\";\n");
+
+    assert_eq!(
+        report,
+        "error: Variable D already declared
+ --> test.dgf:3:8
+  |
+1 | define D \"m\";
+  |        - previously declared here
+...
+3 | define D \"s\";
+  |        ^ declared again here"
+    );
+}
+
+#[test]
+fn expression_spans_cover_the_whole_expression() {
+    let report = report("define D \"m\"; define T \"s\";\nassert (D T)2 / T D;\n");
+
+    assert_eq!(
+        report,
+        "error: Assertion failed: expected m2 s, got m, off by a factor of m s
+ --> test.dgf:2:1
+  |
+2 | assert (D T)2 / T D;
+  | ^^^^^^^^^^^^^^^^^^^^
+  |        ---------- this is m2 s
+  |                   - this is m
+  = note: in axioms, m2 s on the left and m on the right
+  = note: exponent of each axiom:
+          axiom  left  right
+          m         2      1  ≠
+          s         1      0  ≠"
+    );
+}
+
+#[test]
+fn failed_assertion_compares_the_axioms() {
+    let report = report(
+        "define D \"m\"; define T \"s\"; define M \"kg\";
+let Vit = D / T; let F = M D T-2;
+assert F (M Vit);",
+    );
+
+    assert!(report.starts_with(
+        "error: Assertion failed: expected m kg s-2, got kg Vit, off by a factor of s-1\n"
+    ));
+    assert!(report.ends_with(
+        "  = note: in axioms, m kg s-2 on the left and m kg s-1 on the right
+  = note: exponent of each axiom:
+          axiom  left  right
+          m         1      1
+          kg        1      1
+          s        -2     -1  ≠"
+    ));
+}
+
+#[test]
+fn json_report_has_the_spans_labels_and_suggestions() {
+    let mut interpreter = Interpreter::default();
+    let program = "define D \"m\";\ndefine Vit \"m/s\";\nprint Vt;\ndefine D \"s\";\n";
+    let program = common::parse(&mut interpreter, "dir\\\"test\".dgf", program).unwrap();
+    let program = interpreter.resolve(program);
+    let errors = program.errors();
+    let sources = interpreter.sources();
+
+    assert_eq!(
+        sources.report_json(errors[0]),
+        r#"{"kind":"variable_not_declared","level":"error","message":"Variable Vt not declared","file":"dir\\\"test\".dgf","start":{"line":3,"column":7},"end":{"line":3,"column":9},"label":"not declared","related":[],"suggestions":["Vit"],"notes":[]}"#
+    );
+    assert_eq!(
+        sources.report_json(errors[1]),
+        r#"{"kind":"variable_already_declared","level":"error","message":"Variable D already declared","file":"dir\\\"test\".dgf","start":{"line":4,"column":8},"end":{"line":4,"column":9},"label":"declared again here","related":[{"file":"dir\\\"test\".dgf","start":{"line":1,"column":8},"end":{"line":1,"column":9},"message":"previously declared here"}],"suggestions":[],"notes":[]}"#
+    );
+}
@@ -0,0 +1,146 @@
+//! Each limit of an interpreter stops the parsing or the evaluation with its own error.
+
+mod common;
+
+use std::time::Duration;
+
+use digify::{
+    DigifyError, DigifyParser, ErrorKind, Interpreter, Limits, Symbol, Unit,
+    DEFAULT_MAX_SEARCH_CANDIDATES,
+};
+
+/// Error of `program`, evaluated within `limits` after the declaration of the axioms
+fn error(limits: Limits, program: &str) -> DigifyError {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(std::io::sink());
+    interpreter.eval_str(common::AXIOMS).unwrap();
+    interpreter.set_limits(limits);
+
+    match interpreter.eval_str(program) {
+        Err(error) => error,
+        Ok(()) => panic!("{} is within the limits", program),
+    }
+}
+
+#[test]
+fn statements() {
+    let limits = Limits {
+        max_statements: Some(2),
+        ..Limits::default()
+    };
+    let error = error(limits, "let A = D; let B = T; let C = M;");
+
+    assert!(matches!(error.kind(), ErrorKind::StatementLimit(2)));
+}
+
+#[test]
+fn idents() {
+    let limits = Limits {
+        max_idents: Some(2),
+        ..Limits::default()
+    };
+    let error = error(limits, "let A = D T; print A M D-1;");
+
+    assert!(matches!(error.kind(), ErrorKind::IdentLimit(2)));
+}
+
+#[test]
+fn written_exponent() {
+    let limits = Limits {
+        max_exponent: Some(10),
+        ..Limits::default()
+    };
+    let error = error(limits, "let A = D11;");
+
+    assert!(matches!(error.kind(), ErrorKind::ExponentLimit(10)));
+}
+
+#[test]
+fn computed_exponent() {
+    let limits = Limits {
+        max_exponent: Some(10),
+        ..Limits::default()
+    };
+    let error = error(limits, "let A = D5; let B = A3;");
+
+    assert!(matches!(error.kind(), ErrorKind::ExponentLimit(10)));
+}
+
+#[test]
+fn exponents_given_by_hand() {
+    let exponents = [(Symbol::intern("D"), i32::MAX), (Symbol::intern("D"), 1)];
+    assert!(Unit::checked_from_exponents(exponents).is_none());
+
+    let exponents = [(Symbol::intern("D"), i32::MAX), (Symbol::intern("D"), -1)];
+    let unit = Unit::checked_from_exponents(exponents).unwrap();
+    assert_eq!(unit.exponents(), [(Symbol::intern("D"), i32::MAX - 1)]);
+}
+
+#[test]
+fn depth() {
+    let limits = Limits {
+        max_depth: Some(3),
+        ..Limits::default()
+    };
+    let error = error(limits, "print ((((D))));");
+
+    assert!(matches!(error.kind(), ErrorKind::DepthLimit(3)));
+}
+
+#[test]
+fn time() {
+    let limits = Limits {
+        time_budget: Some(Duration::from_millis(100)),
+        max_search_candidates: None,
+        ..Limits::default()
+    };
+    // Only D, T and M are independent: 19^6 choices of the exponents of the others, far
+    // more than what fits in the budget
+    let program = "let A = D; let B = D; let C = D; let E = D; let F = D; let G = D;
+        search D with D, T, M, A, B, C, E, F, G max 9;";
+    let error = error(limits, program);
+
+    assert!(matches!(error.kind(), ErrorKind::TimeLimit(_)));
+}
+
+#[test]
+fn search_candidates() {
+    let limits = Limits {
+        max_search_candidates: Some(1000),
+        ..Limits::default()
+    };
+    // 19^6 choices of the free exponents, refused before any is tried
+    let program = "let A = D; let B = D; let C = D; let E = D; let F = D; let G = D;
+        search D with D, T, M, A, B, C, E, F, G max 9;";
+    let error = error(limits, program);
+
+    assert!(matches!(error.kind(), ErrorKind::SearchLimit(1000)));
+}
+
+#[test]
+fn searches_are_bounded_by_default() {
+    let program = "let A = D; let B = D; let C = D; let E = D; let F = D; let G = D;
+        search D with D, T, M, A, B, C, E, F, G max 1000;";
+    let error = error(Limits::default(), program);
+
+    assert!(matches!(
+        error.kind(),
+        ErrorKind::SearchLimit(DEFAULT_MAX_SEARCH_CANDIDATES)
+    ));
+}
+
+#[test]
+fn parsing_time() {
+    let limits = Limits {
+        time_budget: Some(Duration::from_millis(1)),
+        ..Limits::default()
+    };
+    let mut interpreter = Interpreter::default();
+    let program = "let A = D +;\n".repeat(20_000);
+    let file = interpreter.sources_mut().add("<test>", program);
+
+    // The parsing stops instead of reporting every syntax error
+    let errors = DigifyParser::parse_to_ast(interpreter.sources(), file, &limits).unwrap_err();
+    assert!(errors.len() < 20_000);
+    assert!(matches!(errors.last().unwrap().kind(), ErrorKind::TimeLimit(_)));
+}
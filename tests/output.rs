@@ -0,0 +1,65 @@
+//! Prints go to the output given to the interpreter, and a broken output stops the
+//! evaluation.
+
+mod common;
+
+use std::io::{self, Write};
+
+use common::output;
+use digify::{ErrorKind, Interpreter};
+
+/// Output refusing every write
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn strings_are_printed_as_written() {
+    assert_eq!(output("print \"==== DEBUT ====\";"), "==== DEBUT ====\n");
+    assert_eq!(output("print \"D / T; % \";"), "D / T; % \n");
+}
+
+#[test]
+fn units_are_printed_as_written_unless_simplified() {
+    let program = "let Vit = D / T; let Acc = Vit / T; print Acc; print %Acc; print %Vit / T;";
+
+    assert_eq!(output(program), "Vit s-1\nm s-2\nm s-2\n");
+}
+
+#[test]
+fn each_interpreter_has_its_own_output() {
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut first);
+    interpreter.eval_str("define D \"m\"; print D;").unwrap();
+    // The output can be changed between two programs
+    interpreter.set_output(&mut second);
+    interpreter.eval_str("print \"D\"; print D2;").unwrap();
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(first).unwrap(), "m\n");
+    assert_eq!(String::from_utf8(second).unwrap(), "D\nm2\n");
+}
+
+#[test]
+fn broken_output_stops_the_evaluation() {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(Broken);
+
+    let error = interpreter
+        .eval_str("define D \"m\"; print D; let A = D;")
+        .unwrap_err();
+
+    assert!(matches!(error.kind(), ErrorKind::Output(_)));
+    assert_eq!(error.to_string(), "Can not print: closed");
+    assert!(interpreter.lookup("A").is_none());
+}
@@ -0,0 +1,65 @@
+//! A standalone expression is parsed into a unit with the variables declared so far.
+
+mod common;
+
+use digify::{ErrorKind, Interpreter, Symbol, Unit};
+
+fn interpreter() -> Interpreter<'static> {
+    let mut interpreter = Interpreter::default();
+    interpreter
+        .eval_str(format!("{} let Vit = D / T; let N = M D T-2;", common::AXIOMS))
+        .unwrap();
+
+    interpreter
+}
+
+#[test]
+fn units_keep_their_variables() {
+    let mut interpreter = interpreter();
+
+    let unit = interpreter.parse_unit("Vit / T").unwrap();
+    assert_eq!(interpreter.render(&unit), "Vit s-1");
+
+    let axioms = Unit::from_exponents([(Symbol::intern("D"), 1), (Symbol::intern("T"), -2)]);
+    assert_eq!(interpreter.simplify(&unit), Some(axioms));
+}
+
+#[test]
+fn equal_units_simplify_to_the_same_axioms() {
+    let mut interpreter = interpreter();
+
+    let force = interpreter.parse_unit("N").unwrap();
+    let written = interpreter.parse_unit("M D T-2").unwrap();
+    assert_ne!(force, written);
+    assert_eq!(interpreter.simplify(&force), interpreter.simplify(&written));
+    assert_eq!(interpreter.render(&written), "m kg s-2");
+}
+
+#[test]
+fn undeclared_variables_are_errors() {
+    let mut interpreter = interpreter();
+
+    let error = interpreter.parse_unit("Vt D").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::VariableNotDeclared(_)));
+    assert_eq!(error.suggestions(), ["Vit"]);
+    assert_eq!(
+        interpreter.sources().report(&error),
+        "error: Variable Vt not declared
+ --> <unit>:1:1
+  |
+1 | Vt D
+  | ^^ not declared
+  = help: did you mean `Vit`?"
+    );
+}
+
+#[test]
+fn only_an_expression_is_a_unit() {
+    let mut interpreter = interpreter();
+
+    let error = interpreter.parse_unit("print D;").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::UnexpectedToken { .. }));
+    // A statement is rejected before it could declare anything
+    assert!(interpreter.parse_unit("let A = D").is_err());
+    assert!(interpreter.lookup("A").is_none());
+}
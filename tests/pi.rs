@@ -0,0 +1,40 @@
+//! `pi` gives a basis of the independent dimensionless products of variables.
+
+mod common;
+
+use common::output;
+use digify::{Interpreter, Item, Span, Symbol};
+
+#[test]
+fn reynolds_number() {
+    let program = "let Vit = D / T; let Rho = M / D3; let Mu = M / (D * T);
+        pi Rho, Vit, D, Mu;";
+
+    assert_eq!(output(program), "π1 = Rho Vit D Mu-1\n");
+}
+
+#[test]
+fn one_group_per_free_variable() {
+    let program = "let Vit = D / T; let G = D / T2; pi D, T, Vit, G;";
+
+    assert_eq!(output(program), "π1 = D T-1 Vit-1\nπ2 = D T-2 G-1\n");
+}
+
+#[test]
+fn independent_variables_have_no_group() {
+    assert_eq!(output("pi D, T, M;"), "No dimensionless group\n");
+}
+
+#[test]
+fn groups_from_the_library() {
+    let mut interpreter = Interpreter::default();
+    interpreter
+        .eval_str(format!("{} let G = D / T2;", common::AXIOMS))
+        .unwrap();
+    let variables = ["T", "D", "G"].map(|ident| Item::new(Symbol::intern(ident), Span::default()));
+
+    let groups = interpreter.pi_groups(&variables).unwrap();
+    let groups = groups.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    assert_eq!(groups, ["T2 D-1 G"]);
+}
@@ -0,0 +1,53 @@
+//! `prefer` factors the printed units into the preferred ones. Once a unit is preferred,
+//! every print is simplified down to the axioms before being factored.
+
+mod common;
+
+use common::output;
+use digify::Interpreter;
+
+const UNITS: &str = "let Vit = D / T; let N = M D T-2; let J = N D; let W = J / T;";
+
+#[test]
+fn units_are_factored_into_the_preferred_ones() {
+    let program = format!(
+        "{} print %(M D T-2); prefer N, J, W; print %(M D T-2); print M D2 T-3; print N T;
+         print J D;",
+        UNITS
+    );
+
+    assert_eq!(output(&program), "m kg s-2\nN\nW\nN s\nm J\n");
+}
+
+#[test]
+fn every_print_is_simplified_once_a_unit_is_preferred() {
+    // Vit is kept as written before the `prefer`, not after, even though J does not fit
+    let program = format!("{} print Vit T-1; prefer J; print Vit T-1; print Vit;", UNITS);
+
+    assert_eq!(output(&program), "Vit s-1\nm s-2\nm s-1\n");
+}
+
+#[test]
+fn a_factor_is_kept_only_if_it_is_simpler() {
+    // N kg-1 has a total exponent of 2, below the 3 of m s-2
+    let program = format!("{} prefer N; print Vit T-1; print D;", UNITS);
+
+    assert_eq!(output(&program), "N kg-1\nm\n");
+}
+
+#[test]
+fn preferred_units_end_with_their_scope() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    interpreter
+        .eval_str(format!("{} {}", common::AXIOMS, UNITS))
+        .unwrap();
+
+    let block = common::parse(&mut interpreter, "<block>", "prefer W; print J / T;").unwrap();
+    interpreter.eval(block).unwrap();
+    interpreter.eval_str("print J / T;").unwrap();
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "W\nJ s-1\n");
+}
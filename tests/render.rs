@@ -0,0 +1,70 @@
+//! Each style prints the same unit, with the symbols of the axioms and the idents of the
+//! other units.
+
+mod common;
+
+use common::output;
+use digify::Renderer;
+
+#[test]
+fn styles() {
+    let program = "let Acc = D T-2; print M Acc as ascii; print M Acc as unicode;
+        print M Acc as fraction; print M Acc as latex; print M Acc as siunitx;
+        print M Acc as canonical;";
+
+    assert_eq!(
+        output(program),
+        "Acc kg
+Acc·kg
+Acc·kg
+\\mathrm{Acc}\\,\\mathrm{kg}
+\\si{\\text{Acc}\\kilogram}
+Acc M
+"
+    );
+}
+
+#[test]
+fn axioms_only() {
+    let program = "print M D T-2 as ascii; print M D T-2 as unicode; print M D T-2 as fraction;
+        print M D T-2 as latex; print M D T-2 as siunitx; print M D T-2 as canonical;";
+
+    assert_eq!(
+        output(program),
+        "m kg s-2
+m·kg·s⁻²
+m·kg/s²
+\\mathrm{m}\\,\\mathrm{kg}\\,\\mathrm{s}^{-2}
+\\si{\\metre\\kilogram\\per\\second\\squared}
+D M T-2
+"
+    );
+}
+
+#[test]
+fn dimensionless() {
+    assert_eq!(output("print D / D as fraction; print D / D as siunitx;"), "1\n\\si{1}\n");
+}
+
+#[test]
+fn derived_units_are_not_si_units() {
+    // The F of the program is a force, not a farad
+    assert_eq!(output("let F = M D T-2; print F * D as siunitx;"), "\\si{\\metre\\text{F}}\n");
+    assert_eq!(output("define F \"F\"; print F * D as siunitx;"), "\\si{\\metre\\farad}\n");
+}
+
+#[test]
+fn symbols_given_directly() {
+    let factors = [("m", 1), ("s", -2)];
+
+    assert_eq!(Renderer::Fraction.render(&factors), "m/s²");
+    assert_eq!(Renderer::Siunitx.render(&factors), "\\si{\\metre\\per\\second\\squared}");
+}
+
+#[test]
+fn styles_by_name() {
+    assert_eq!("fraction".parse::<Renderer>(), Ok(Renderer::Fraction));
+
+    let error = "latek".parse::<Renderer>().unwrap_err();
+    assert_eq!(error.to_string(), "Unknown render style: latek");
+}
@@ -0,0 +1,122 @@
+//! Names are resolved before evaluation: every name error is reported at once, and a unit
+//! keeps referring to the variables visible where it was written.
+
+mod common;
+
+use digify::{ErrorKind, Interpreter, Item, Program, Span, Symbol};
+
+#[test]
+fn every_name_error_is_reported() {
+    let mut interpreter = Interpreter::default();
+    let program: Program = common::resolve(
+        &mut interpreter,
+        "print A; let B = C; define D \"m\"; define D \"s\"; print B D;",
+    );
+    let messages = program.errors().iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    assert_eq!(
+        messages,
+        [
+            "Variable A not declared",
+            "Variable C not declared",
+            "Variable D already declared",
+        ]
+    );
+    // Nothing was evaluated
+    assert!(interpreter.lookup("D").is_none());
+}
+
+#[test]
+fn name_errors_do_not_stop_the_evaluation() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    let program = common::resolve(
+        &mut interpreter,
+        "define D \"m\"; define T \"s\"; print Vt; let A = Vt D; assert D T; print A; print D;",
+    );
+
+    // The name errors are reported with the evaluation errors, in the order of the program,
+    // and the statements using A are skipped
+    let errors = interpreter.run(program).unwrap_err();
+    let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0], "Variable Vt not declared");
+    assert_eq!(messages[1], "Variable Vt not declared");
+    assert!(messages[2].starts_with("Assertion failed"));
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "m\n");
+}
+
+#[test]
+fn shadowing_keeps_outer_units() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    interpreter.eval_str("define D \"m\"; let A = D;").unwrap();
+
+    // A program evaluated with `eval` has its own scope, where `D` is shadowed
+    let block = common::parse(
+        &mut interpreter,
+        "<block>",
+        "define D \"s\"; print %A; print A D; print %(A D); let A = A D; print %A;",
+    )
+    .unwrap();
+    interpreter.eval(block).unwrap();
+
+    let error = interpreter.parse_unit("Vit").unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::VariableNotDeclared(_)));
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "m\nA s\nm s\nm s\n");
+}
+
+#[test]
+fn a_failed_redeclaration_keeps_the_previous_variable() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    let program = common::resolve(
+        &mut interpreter,
+        "define D \"m\"; define T \"s\"; let A = D; let A = T; print A; assert A D;",
+    );
+
+    // Only the redeclaration fails, the statements using A run with its first variable
+    let errors = interpreter.run(program).unwrap_err();
+    let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(messages, ["Variable A already declared"]);
+    let unit = interpreter.parse_unit("A").unwrap();
+    assert_eq!(interpreter.render(&unit), "m");
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "m\n");
+}
+
+#[test]
+fn lists_of_variables_have_no_repeated_name() {
+    let mut interpreter = Interpreter::default();
+    interpreter.eval_str(common::AXIOMS).unwrap();
+    let program = common::resolve(
+        &mut interpreter,
+        "pi D, T, D; print %[D, T, D] D; search D with D, T, T; prefer D, M; pi M, D, T;",
+    );
+    let errors = program.errors();
+
+    let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "Variable D given twice in the list",
+            "Variable D given twice in the list",
+            "Variable T given twice in the list",
+        ]
+    );
+    assert!(matches!(errors[0].kind(), ErrorKind::RepeatedVariable(_)));
+    assert_eq!(errors[0].labels()[0].message, "first given here");
+
+    // The library checks its lists the same way
+    let variables = ["D", "D"].map(|ident| Item::new(Symbol::intern(ident), Span::default()));
+    let error = interpreter.pi_groups(&variables).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::RepeatedVariable(_)));
+}
@@ -0,0 +1,127 @@
+//! `parse(render(u)) == u` for the units built from the examples, with the canonical style.
+//!
+//! Each example is extended with prints of its idents and of random products of them, as is
+//! and simplified, in the canonical style. Each printed text is then printed again: as the
+//! canonical text of a unit is unique, both prints must be equal.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const MARKER: &str = "@@ roundtrip @@";
+const RANDOM_UNITS: usize = 50;
+
+fn run(name: &str, source: &str, flags: &[&str]) -> Vec<String> {
+    let path = std::env::temp_dir().join(format!("digify_roundtrip_{}.dgf", name));
+    fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_digify"))
+        .args(flags)
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}:\n{}",
+        name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .skip_while(|line| *line != MARKER)
+        .skip(1)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Idents declared by `define` or `let` in `source`
+fn declared_idents(source: &str) -> Vec<&str> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("define ")
+                .or_else(|| line.strip_prefix("let "))
+        })
+        .filter_map(|rest| rest.split([' ', '=']).next())
+        .collect()
+}
+
+/// Flags given in the first line of an example, like `// digify --sort-definitions <file>`
+fn flags(source: &str) -> Vec<&str> {
+    source
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("// digify "))
+        .map(|command| command.split_whitespace().filter(|arg| arg.starts_with('-')).collect())
+        .unwrap_or_default()
+}
+
+/// Small xorshift generator, enough to build varied units
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+fn random_product(random: &mut Random, idents: &[&str]) -> String {
+    let factors = (0..1 + random.next(4))
+        .map(|_| {
+            let ident = idents[random.next(idents.len())];
+            let power = [-3, -2, -1, 1, 2, 3][random.next(6)];
+            format!("{}{}", ident, power)
+        })
+        .collect::<Vec<_>>();
+
+    // Mix explicit and implicit multiplications
+    let separator = if random.next(2) == 0 { " * " } else { " " };
+    factors.join(separator)
+}
+
+#[test]
+fn canonical_units_parse_back() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut random = Random(0x5eed);
+
+    for entry in fs::read_dir(examples).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|extension| extension != "dgf") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&path).unwrap();
+        let idents = declared_idents(&source);
+        if idents.is_empty() {
+            continue;
+        }
+
+        let mut units = idents.iter().map(|ident| ident.to_string()).collect::<Vec<_>>();
+        units.extend((0..RANDOM_UNITS).map(|_| random_product(&mut random, &idents)));
+        units.extend(units.clone().iter().map(|unit| format!("%({})", unit)));
+
+        // Print inside the block of the example, before its last statement
+        let (head, tail) = source.trim_end().rsplit_once("print").unwrap();
+        let program = |units: &[String]| {
+            let mut program = format!("{}print \"{}\";\n", head, MARKER);
+            for unit in units {
+                program.push_str(&format!("print {} as canonical;\n", unit));
+            }
+            program.push_str(&format!("print{}\n", tail));
+            program
+        };
+
+        let flags = flags(&source);
+        let rendered = run(name, &program(&units), &flags);
+        let rendered = &rendered[..units.len()];
+        let reparsed = run(name, &program(rendered), &flags);
+
+        assert_eq!(rendered, &reparsed[..units.len()], "in example {}", name);
+    }
+}
@@ -0,0 +1,23 @@
+//! A search only enumerates the exponents left free by the dimensions, and keeps the
+//! simplest products.
+
+mod common;
+
+use common::output;
+
+#[test]
+fn independent_variables_are_solved() {
+    // A single product fits, whatever the bound
+    let output = output("let F = M D T-2; search F2 D-1 with D, T, M max 1000000;");
+
+    assert_eq!(output, "D T-4 M2\n");
+}
+
+#[test]
+fn simplest_products_first() {
+    let output = output("let Vit = D / T; search D with D, T, Vit max 9;");
+    let lines = output.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[..2], ["D", "T Vit"]);
+}
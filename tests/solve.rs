@@ -0,0 +1,55 @@
+//! `solve` finds the unit of an unknown from an equation over the axioms, or says why there
+//! is none.
+
+mod common;
+
+use common::output;
+use digify::{ErrorKind, Interpreter};
+
+fn no_solution(equation: &str) -> String {
+    let mut interpreter = Interpreter::default();
+    interpreter.eval_str(common::AXIOMS).unwrap();
+    let error = interpreter.eval_str(equation).unwrap_err();
+
+    match error.kind() {
+        ErrorKind::NoSolution(..) => error.to_string(),
+        kind => panic!("unexpected error: {}", kind),
+    }
+}
+
+#[test]
+fn unknowns_are_solved_on_either_side() {
+    assert_eq!(output("solve X: X * T2 == D; print X;"), "m s-2\n");
+    assert_eq!(output("let Acc = D / T2; solve Y: Y * T == Acc; print Y;"), "m s-3\n");
+    assert_eq!(output("solve Z: M == Z D3; print Z;"), "kg m-3\n");
+}
+
+#[test]
+fn powers_of_the_unknown_are_divided() {
+    assert_eq!(output("solve Z: Z2 == D2 / T2; print Z;"), "m s-1\n");
+    assert_eq!(output("solve Z: Z-3 == T3; print Z;"), "s-1\n");
+}
+
+#[test]
+fn solved_unknowns_are_variables() {
+    let program =
+        "let Acc = D / T2; solve X: X * T2 == D; assert X Acc; print X T2; print %(X T2);";
+
+    assert_eq!(output(program), "s2 X\nm\n");
+}
+
+#[test]
+fn equations_without_solution() {
+    assert_eq!(
+        no_solution("solve X: X2 == D;"),
+        "No solution for X: the exponent of D is not a multiple of 2"
+    );
+    assert_eq!(
+        no_solution("solve X: X X-1 == D;"),
+        "No solution for X: X cancels out of the equation"
+    );
+    assert_eq!(
+        no_solution("solve X: D == T;"),
+        "No solution for X: X cancels out of the equation"
+    );
+}
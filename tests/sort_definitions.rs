@@ -0,0 +1,65 @@
+//! With sorted definitions, a `let` may come before the definitions it references.
+
+mod common;
+
+use digify::{ErrorKind, Interpreter};
+
+#[test]
+fn definitions_are_evaluated_in_dependency_order() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    interpreter.set_sort_definitions(true);
+
+    interpreter
+        .eval_str("print %Vit; let Vit = D / T; define T \"s\"; define D \"m\";")
+        .unwrap();
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "m s-1\n");
+}
+
+#[test]
+fn solved_unknowns_are_definitions() {
+    let mut output = Vec::new();
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(&mut output);
+    interpreter.set_sort_definitions(true);
+
+    interpreter
+        .eval_str("let Y = X * T; print %Y; define D \"m\"; define T \"s\"; solve X: X T == D;")
+        .unwrap();
+    drop(interpreter);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "m\n");
+}
+
+#[test]
+fn cycles_are_reported() {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_sort_definitions(true);
+    let program = common::resolve(
+        &mut interpreter,
+        "let A = B; let B = A D; define D \"m\"; let C = C; let E = A;",
+    );
+    let errors = program.errors();
+    let cycles = errors
+        .iter()
+        .map(|error| match error.kind() {
+            ErrorKind::CyclicDefinitions(names) => names.join(" "),
+            kind => panic!("unexpected error: {}", kind),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(cycles, ["A B A", "C C"]);
+}
+
+#[test]
+fn order_matters_by_default() {
+    let mut interpreter = Interpreter::default();
+    let error = interpreter
+        .eval_str("let Vit = D; define D \"m\";")
+        .unwrap_err();
+
+    assert!(matches!(error.kind(), ErrorKind::VariableNotDeclared(_)));
+}
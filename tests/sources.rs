@@ -0,0 +1,42 @@
+//! Files can leave the source map, so that a long-running host does not keep every input.
+
+mod common;
+
+use digify::Interpreter;
+
+#[test]
+fn removed_files_are_not_reported() {
+    let mut interpreter = Interpreter::default();
+    let program = common::parse(&mut interpreter, "<line 1>", "define D \"m\";").unwrap();
+    let file = program.span.file();
+    interpreter.load(program).unwrap();
+    assert!(interpreter.sources_mut().remove(file));
+    assert!(!interpreter.sources_mut().remove(file));
+
+    // The slot of the removed file is reused under an other id
+    let program = common::parse(&mut interpreter, "<line 2>", "define D \"s\";").unwrap();
+    assert_ne!(program.span.file(), file);
+    let error = interpreter.load(program).unwrap_err();
+
+    // The first declaration is gone with its file, only the second one is shown
+    assert!(!interpreter.sources().contains(error.labels()[0].span));
+    assert_eq!(
+        interpreter.sources().report(&error),
+        "error: Variable D already declared
+ --> <line 2>:1:8
+  |
+1 | define D \"s\";
+  |        ^ declared again here"
+    );
+}
+
+#[test]
+fn units_only_keep_the_last_expression() {
+    let mut interpreter = Interpreter::default();
+    interpreter.eval_str("define D \"m\";").unwrap();
+
+    let error = interpreter.parse_unit("D /").unwrap_err();
+    assert!(interpreter.sources().contains(error.span()));
+    interpreter.parse_unit("D2").unwrap();
+    assert!(!interpreter.sources().contains(error.span()));
+}
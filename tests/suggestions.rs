@@ -0,0 +1,40 @@
+//! Undeclared names come with the visible names that may have been meant.
+
+mod common;
+
+use digify::Interpreter;
+
+fn suggestions(program: &str) -> Vec<String> {
+    let mut interpreter = Interpreter::default();
+    interpreter.set_output(std::io::sink());
+    let program = common::resolve(&mut interpreter, program);
+    let errors = program.errors();
+    assert_eq!(errors.len(), 1);
+
+    errors[0].suggestions().to_vec()
+}
+
+#[test]
+fn close_names_are_suggested() {
+    let program = "define D \"m\"; define T \"s\"; let Vit = D / T;";
+
+    assert_eq!(suggestions(&format!("{} print Vt;", program)), ["Vit"]);
+    assert_eq!(suggestions(&format!("{} print Vitesse;", program)), [] as [&str; 0]);
+    assert_eq!(suggestions(&format!("{} print vit;", program)), ["Vit"]);
+}
+
+#[test]
+fn axioms_are_suggested_for_their_symbol() {
+    assert_eq!(suggestions("define D \"m\"; let Vit = m;"), ["D"]);
+}
+
+#[test]
+fn names_of_outer_scopes_are_suggested() {
+    let mut interpreter = Interpreter::default();
+    interpreter.eval_str("define Masse \"kg\";").unwrap();
+    let program = common::resolve(&mut interpreter, "print Mase;");
+    let errors = program.errors();
+
+    assert_eq!(errors[0].suggestions(), ["Masse"]);
+    assert!(interpreter.sources().report(errors[0]).ends_with("= help: did you mean `Masse`?"));
+}
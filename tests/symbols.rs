@@ -0,0 +1,45 @@
+//! Symbols are shared by name, except the fresh ones given to shadowing variables.
+
+use digify::{Interpreter, Symbol, Unit};
+
+#[test]
+fn interned_symbols_are_shared() {
+    let symbol = Symbol::intern("Vit");
+    assert_eq!(symbol, Symbol::from("Vit"));
+    assert_ne!(symbol, Symbol::intern("vit"));
+    assert_eq!(symbol.to_string(), "Vit");
+}
+
+#[test]
+fn fresh_symbols_are_distinct() {
+    let symbol = Symbol::intern("Vit");
+    let fresh = Symbol::fresh("Vit");
+    assert_ne!(symbol, fresh);
+    assert_ne!(fresh, Symbol::fresh("Vit"));
+    assert_eq!(fresh.as_str(), "Vit");
+    assert!(symbol < fresh);
+}
+
+#[test]
+fn symbols_and_units_cross_threads() {
+    fn send<T: Send>(value: T) -> T {
+        value
+    }
+
+    let symbol = std::thread::spawn(|| Symbol::intern("Vit")).join().unwrap();
+    assert_eq!(symbol, Symbol::intern("Vit"));
+    assert_eq!(send(symbol).as_str(), "Vit");
+
+    let unit = send(Unit::from_exponents([(symbol, 1)]));
+    let unit = std::thread::spawn(move || unit).join().unwrap();
+    assert_eq!(unit.exponent(symbol), 1);
+
+    // An interpreter can be handed to the thread serving its requests
+    let mut interpreter = send(Interpreter::default());
+    interpreter.set_output(std::io::sink());
+    let interpreter = std::thread::spawn(move || {
+        interpreter.eval_str("define D \"m\"; let Vit = D;").unwrap();
+        interpreter
+    });
+    assert!(interpreter.join().unwrap().lookup("Vit").is_some());
+}
@@ -0,0 +1,75 @@
+//! Syntax errors name what was expected, and the parsing goes on after each statement.
+
+mod common;
+
+use digify::{ErrorKind, Interpreter};
+
+fn messages(program: &str) -> Vec<String> {
+    let mut interpreter = Interpreter::default();
+
+    common::parse(&mut interpreter, "<test>", program)
+        .unwrap_err()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn every_statement_is_checked() {
+    let messages = messages(
+        "define D \"m\";\nlet A = D +;\nprint D;\nsolve X: X = D;\npi A B;\nlet let = D;\n",
+    );
+
+    assert_eq!(
+        messages,
+        [
+            "Syntax error: expected `*`, `/`, an expression, an exponent or `;`, found `+`",
+            "Syntax error: expected `*`, `/`, an expression, an exponent or `==`, found `=`",
+            "Syntax error: expected `,` or `;`, found `B`",
+            "Syntax error: `let` is a keyword and can not name a variable",
+        ]
+    );
+}
+
+#[test]
+fn end_of_file_and_strings() {
+    assert_eq!(
+        messages("define D \"m\";\nprint D"),
+        ["Syntax error: expected `as`, `*`, `/`, an expression, an exponent or `;`, found the end of the file"]
+    );
+    assert_eq!(
+        messages("print \"D;\nprint D;"),
+        ["Syntax error: string never closed"]
+    );
+}
+
+#[test]
+fn exponents_are_written_without_spaces() {
+    // `D - 1` is not the exponent -1 of D, nor an overflowing one
+    assert_eq!(
+        messages("define D \"m\";\nprint D - 1;"),
+        ["Syntax error: expected `as`, `*`, `/`, an expression, an exponent or `;`, found `-`"]
+    );
+}
+
+#[test]
+fn each_error_is_parsed_again_alone() {
+    // Each statement is parsed again on its own, not the whole file for each error
+    let program = "define D \"m\";\n".to_owned() + &"let A = D +;\nlet B = (D;\n".repeat(2000);
+    let messages = messages(&program);
+
+    assert_eq!(messages.len(), 4000);
+    assert_eq!(
+        messages[3999],
+        "Syntax error: expected `*`, `/`, an expression, an exponent or `)`, found `;`"
+    );
+}
+
+#[test]
+fn units_have_syntax_errors_too() {
+    let mut interpreter = Interpreter::default();
+    let error = interpreter.parse_unit("kg m /").unwrap_err();
+
+    assert!(matches!(error.kind(), ErrorKind::UnexpectedToken { .. }));
+    assert_eq!(error.to_string(), "Syntax error: expected an expression, found the end of the file");
+}